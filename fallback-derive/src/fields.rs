@@ -0,0 +1,534 @@
+use crate::options::FallbackOptions;
+use proc_macro2::{Ident, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, Field, Fields, GenericParam, Generics, Index, LitStr, Member, Path, Type,
+    Visibility,
+};
+
+/// The shape of the deriving struct or variant.
+pub enum FieldsStyle {
+    Named,
+    Unnamed,
+    Unit,
+}
+
+/// The fields of the deriving struct or variant.
+pub struct FallbackFields {
+    style: FieldsStyle,
+    fields: Vec<FallbackField>,
+}
+
+impl FallbackFields {
+    pub fn new(fields: Fields, options: &FallbackOptions) -> syn::Result<Self> {
+        let style = match &fields {
+            Fields::Named(_) => FieldsStyle::Named,
+            Fields::Unnamed(_) => FieldsStyle::Unnamed,
+            Fields::Unit => FieldsStyle::Unit,
+        };
+        let mut spec_index = 0;
+        let fields = collect_results(fields.into_iter().enumerate().map(|(i, field)| {
+            let field = FallbackField::new(field, i, spec_index, options)?;
+            if field.mode != FieldMode::Skip {
+                spec_index += 1;
+            }
+            Ok(field)
+        }))?;
+        Ok(Self { style, fields })
+    }
+
+    pub fn style(&self) -> &FieldsStyle {
+        &self.style
+    }
+
+    /// Iterates the fields which show up in the spec types.
+    pub fn iter(&self) -> impl Iterator<Item = &FallbackField> {
+        self.fields
+            .iter()
+            .filter(|field| field.mode != FieldMode::Skip)
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// The members of the fields in the deriving type.
+    pub fn members(&self) -> Vec<Member> {
+        self.iter().map(|field| field.member.clone()).collect()
+    }
+
+    /// The members of the fields in the spec types.
+    pub fn spec_members(&self) -> Vec<Member> {
+        self.iter().map(|field| field.spec_member.clone()).collect()
+    }
+
+    /// The members of the fields marked with `#[fallback(skip)]`.
+    pub fn skipped_members(&self) -> Vec<Member> {
+        self.fields
+            .iter()
+            .filter(|field| field.mode == FieldMode::Skip)
+            .map(|field| field.member.clone())
+            .collect()
+    }
+
+    /// Indices to access the fields, when they are collected into a tuple.
+    pub fn indices(&self) -> Vec<Index> {
+        (0..self.len()).map(Index::from).collect()
+    }
+
+    /// Locals to bind the fields, when they are destructured.
+    pub fn bindings(&self) -> Vec<Ident> {
+        self.prefixed_bindings("field")
+    }
+
+    /// Locals named `{prefix}_{i}` to bind the fields, when they are destructured.
+    pub fn prefixed_bindings(&self, prefix: &str) -> Vec<Ident> {
+        (0..self.len())
+            .map(|i| Ident::new(&format!("{}_{}", prefix, i), proc_macro2::Span::call_site()))
+            .collect()
+    }
+
+    /// Destructures the deriving type at `path` into [`FallbackFields::bindings`].
+    pub fn pattern(&self, path: TokenStream2) -> TokenStream2 {
+        let members = self.members();
+        let bindings = self.bindings();
+        quote! {#path { #(#members: #bindings ,)* .. }}
+    }
+
+    /// Destructures a spec type at `path` into [`FallbackFields::bindings`].
+    pub fn spec_pattern(&self, path: TokenStream2) -> TokenStream2 {
+        self.spec_pattern_with(path, &self.bindings())
+    }
+
+    /// Destructures a spec type at `path` into `bindings`.
+    pub fn spec_pattern_with(&self, path: TokenStream2, bindings: &[Ident]) -> TokenStream2 {
+        let members = self.spec_members();
+        quote! {#path { #(#members: #bindings ,)* }}
+    }
+
+    /// Lints to allow in the generated impls, where the parameters and locals
+    /// are left unused if there is no field.
+    pub fn allow_unused(&self) -> Option<TokenStream2> {
+        (self.len() == 0).then(|| quote! {#[allow(unused_variables, unused_mut)]})
+    }
+
+    /// Declares a generated struct with the same shape and generics as the deriving struct.
+    pub fn declare(
+        &self,
+        vis: &Visibility,
+        name: &Ident,
+        generics: &Generics,
+        fields: Vec<Field>,
+    ) -> TokenStream2 {
+        let where_clause = &generics.where_clause;
+        match self.style {
+            FieldsStyle::Named => quote! {
+                #vis struct #name #generics #where_clause {
+                    #(#fields ,)*
+                }
+            },
+            FieldsStyle::Unnamed => quote! {
+                #vis struct #name #generics (#(#fields ,)*) #where_clause;
+            },
+            FieldsStyle::Unit => quote! {
+                #vis struct #name #generics #where_clause;
+            },
+        }
+    }
+
+    /// Declares a generated variant with the same shape as the deriving variant.
+    pub fn declare_variant(&self, name: &Ident, fields: Vec<Field>) -> TokenStream2 {
+        match self.style {
+            FieldsStyle::Named => quote! {
+                #name {
+                    #(#fields ,)*
+                }
+            },
+            FieldsStyle::Unnamed => quote! {
+                #name(#(#fields ,)*)
+            },
+            FieldsStyle::Unit => quote! {
+                #name
+            },
+        }
+    }
+}
+
+/// How a field falls back.
+#[derive(PartialEq, Eq)]
+pub enum FieldMode {
+    /// Falls back through all layers.
+    Fallback,
+    /// `#[fallback(skip)]`: left out of the spec types, and resolved to the default value.
+    Skip,
+    /// `#[fallback(data_only)]`: only the data layer, or the first layer of a chain.
+    DataOnly,
+    /// `#[fallback(base_only)]`: only the base layer, or the last layer of a chain.
+    BaseOnly,
+}
+
+/// A field of the deriving struct or variant, with the `#[fallback(...)]` attributes parsed.
+pub struct FallbackField {
+    field: Field,
+    pub member: Member,
+    pub spec_member: Member,
+    pub nested: bool,
+    pub mode: FieldMode,
+    /// The predicate of `empty_is_none` or `when`, which filters the layers.
+    pub predicate: Option<TokenStream2>,
+    /// The function of `merge`, which merges the layers instead of replacing.
+    pub merge: Option<TokenStream2>,
+}
+
+impl FallbackField {
+    fn new(
+        mut field: Field,
+        index: usize,
+        spec_index: usize,
+        options: &FallbackOptions,
+    ) -> syn::Result<Self> {
+        let mut nested = None;
+        let mut mode = None;
+        let mut predicate = None;
+        let mut merge = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("fallback"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let new_predicate = if meta.path.is_ident("empty_is_none") {
+                    let krate = &options.krate;
+                    Some(quote! {#krate::__private::is_not_empty})
+                } else if meta.path.is_ident("when") {
+                    let path = meta.value()?.parse::<LitStr>()?.parse::<Path>()?;
+                    Some(quote! {#path})
+                } else {
+                    None
+                };
+                if let Some(new_predicate) = new_predicate {
+                    if predicate.is_some() {
+                        return Err(meta.error("only one of `empty_is_none` and `when` is allowed"));
+                    }
+                    predicate = Some((meta.path.clone(), new_predicate));
+                    return Ok(());
+                }
+                if meta.path.is_ident("merge") {
+                    if merge.is_some() {
+                        return Err(meta.error("duplicate `merge` attribute"));
+                    }
+                    let krate = &options.krate;
+                    let function = if meta.input.peek(syn::Token![=]) {
+                        let value = meta.value()?.parse::<LitStr>()?;
+                        let strategy = match value.value().as_str() {
+                            "append" => Some(quote! {Append}),
+                            "union" => Some(quote! {Union}),
+                            "deep" => Some(quote! {Deep}),
+                            _ => None,
+                        };
+                        match strategy {
+                            Some(strategy) => quote! {
+                                <#krate::merge::#strategy as #krate::merge::MergeStrategy<_>>::merge
+                            },
+                            None => {
+                                let path = value.parse::<Path>()?;
+                                quote! {#path}
+                            }
+                        }
+                    } else {
+                        quote! {#krate::Merge::merge}
+                    };
+                    merge = Some((meta.path.clone(), function));
+                    return Ok(());
+                }
+                let new_mode = if meta.path.is_ident("nested") {
+                    nested = Some(meta.path.clone());
+                    return Ok(());
+                } else if meta.path.is_ident("skip") {
+                    FieldMode::Skip
+                } else if meta.path.is_ident("data_only") {
+                    FieldMode::DataOnly
+                } else if meta.path.is_ident("base_only") {
+                    FieldMode::BaseOnly
+                } else {
+                    return Err(meta.error("unsupported fallback attribute"));
+                };
+                if mode.is_some() {
+                    return Err(
+                        meta.error("only one of `skip`, `data_only` and `base_only` is allowed")
+                    );
+                }
+                mode = Some(new_mode);
+                Ok(())
+            })?;
+        }
+        if let (Some(nested), Some(_)) = (&nested, &mode) {
+            return Err(syn::Error::new_spanned(
+                nested,
+                "`nested` cannot be combined with `skip`, `data_only` or `base_only`",
+            ));
+        }
+        if let Some((path, _)) = &predicate {
+            if nested.is_some() || mode == Some(FieldMode::Skip) {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "`empty_is_none` and `when` cannot be combined with `nested` or `skip`",
+                ));
+            }
+        }
+        if let Some((path, _)) = &merge {
+            if nested.is_some() || mode.is_some() {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "`merge` cannot be combined with `nested`, `skip`, `data_only` or `base_only`",
+                ));
+            }
+        }
+        options.retain_attrs(&mut field.attrs);
+        let (member, spec_member) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), Member::Named(ident.clone())),
+            None => (
+                Member::Unnamed(Index::from(index)),
+                Member::Unnamed(Index::from(spec_index)),
+            ),
+        };
+        Ok(Self {
+            field,
+            member,
+            spec_member,
+            nested: nested.is_some(),
+            mode: mode.unwrap_or(FieldMode::Fallback),
+            predicate: predicate.map(|(_, predicate)| predicate),
+            merge: merge.map(|(_, merge)| merge),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        match &self.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        }
+    }
+
+    /// Declares the `entry_{name}` method of a spec struct, which gets an `Entry` of the field,
+    /// or nothing if the field is nested, or `base_only` so that it can't be overridden.
+    pub fn entry(&self, krate: &Path) -> Option<TokenStream2> {
+        if self.nested || self.mode == FieldMode::BaseOnly {
+            return None;
+        }
+        let vis = &self.field.vis;
+        let ty = &self.field.ty;
+        let member = &self.spec_member;
+        let method = match &self.member {
+            Member::Named(ident) => format_ident!("entry_{}", ident),
+            Member::Unnamed(index) => format_ident!("entry_{}", index.index),
+        };
+        let doc = format!(
+            "Gets the entry to edit the field `{}` in place.",
+            self.name()
+        );
+        Some(quote! {
+            #[doc = #doc]
+            #vis fn #method(&mut self) -> #krate::Entry<'_, #ty> {
+                #krate::Entry::new(&mut self.#member)
+            }
+        })
+    }
+
+    /// Declares the field in a spec type, wrapping the type with `wrapper`,
+    /// or projecting it to `spec_trait::spec_type` if the field is nested.
+    /// The doc comments are kept if `docs` is set.
+    pub fn declare(
+        &self,
+        wrapper: TokenStream2,
+        spec_trait: TokenStream2,
+        spec_type: TokenStream2,
+        docs: bool,
+    ) -> Field {
+        let mut field = self.field.clone();
+        if !docs {
+            field.attrs.retain(|attr| !attr.path().is_ident("doc"));
+        }
+        field.ty = self.spec_ty(wrapper, spec_trait, spec_type);
+        field
+    }
+
+    /// The type of the field in a spec type, see [`FallbackField::declare`].
+    pub fn spec_ty(
+        &self,
+        wrapper: TokenStream2,
+        spec_trait: TokenStream2,
+        spec_type: TokenStream2,
+    ) -> Type {
+        let ty = &self.field.ty;
+        if self.nested {
+            parse_quote! {<#ty as #spec_trait>::#spec_type}
+        } else {
+            parse_quote! {#wrapper<#ty>}
+        }
+    }
+
+    /// Filters an [`Option`] `value` with the predicate of `empty_is_none` or `when`.
+    pub fn filter(&self, value: TokenStream2) -> TokenStream2 {
+        match &self.predicate {
+            Some(predicate) => quote! {#value.filter(#predicate)},
+            None => value,
+        }
+    }
+
+    /// Wraps the field of the layers `data` and `base` in a [`Fallback`],
+    /// keeping only the layer chosen by `data_only` or `base_only`.
+    pub fn fallback(&self, krate: &Path, data: TokenStream2, base: TokenStream2) -> TokenStream2 {
+        let spec = self.nested.then(|| quote! {.spec()});
+        let data = self.filter(data);
+        let base = self.filter(base);
+        match self.mode {
+            FieldMode::DataOnly => {
+                quote! {#krate::Fallback::new(#data, ::core::option::Option::None)}
+            }
+            FieldMode::BaseOnly => {
+                quote! {#krate::Fallback::new(::core::option::Option::None, #base)}
+            }
+            _ => quote! {#krate::Fallback::new(#data, #base)#spec},
+        }
+    }
+
+    /// Wraps the field of the `layers` in a [`FallbackChain`],
+    /// keeping only the layer chosen by `data_only` or `base_only`.
+    pub fn fallback_chain(&self, krate: &Path, layers: TokenStream2) -> TokenStream2 {
+        let spec = self.nested.then(|| quote! {.spec()});
+        let layers = match &self.predicate {
+            Some(predicate) => quote! {#krate::__private::filter_layers(#layers, #predicate)},
+            None => layers,
+        };
+        match self.mode {
+            FieldMode::DataOnly => {
+                quote! {#krate::FallbackChain::new(#krate::__private::first_layer(#layers))}
+            }
+            FieldMode::BaseOnly => {
+                quote! {#krate::FallbackChain::new(#krate::__private::last_layer(#layers))}
+            }
+            _ => quote! {#krate::FallbackChain::new(#layers)#spec},
+        }
+    }
+
+    /// Pushes the provenance of the wrapped field `value` into `provenance`.
+    pub fn provenance(
+        &self,
+        krate: &Path,
+        value: TokenStream2,
+        source: TokenStream2,
+    ) -> TokenStream2 {
+        let name = self.name();
+        if self.nested {
+            quote! {
+                for (name, source) in #value.provenance() {
+                    provenance.push((#krate::__private::join_field(#name, &name), source));
+                }
+            }
+        } else {
+            quote! {
+                provenance.push((::core::convert::From::from(#name), #value.#source()));
+            }
+        }
+    }
+
+    /// Fallbacks or merges the wrapped field `value` into an [`Option`],
+    /// and records the missing fields into `missing`.
+    pub fn resolve(&self, value: TokenStream2) -> TokenStream2 {
+        let name = self.name();
+        if self.nested {
+            quote! {
+                match #value.resolve() {
+                    ::core::result::Result::Ok(data) => ::core::option::Option::Some(data),
+                    ::core::result::Result::Err(e) => {
+                        missing.extend_nested(#name, e);
+                        ::core::option::Option::None
+                    }
+                }
+            }
+        } else {
+            let fallback = match &self.merge {
+                Some(merge) => quote! {merge_with(#merge)},
+                None => quote! {fallback()},
+            };
+            quote! {
+                {
+                    let data = #value.#fallback;
+                    if data.is_none() {
+                        missing.push(#name);
+                    }
+                    data
+                }
+            }
+        }
+    }
+}
+
+/// Checks that no type or lifetime parameter is used only by the fields marked with
+/// `#[fallback(skip)]`, as they are left out of the generated types,
+/// which would leave the parameter unused there.
+pub fn check_skipped_generics<'a>(
+    generics: &Generics,
+    fields: impl IntoIterator<Item = &'a FallbackFields> + Clone,
+) -> syn::Result<()> {
+    let used_by = |skipped: bool, param: &GenericParam| {
+        fields.clone().into_iter().any(|fields| {
+            fields
+                .fields
+                .iter()
+                .filter(|field| (field.mode == FieldMode::Skip) == skipped)
+                .any(|field| uses_param(field.field.ty.to_token_stream(), param))
+        })
+    };
+    collect_results(generics.params.iter().map(|param| {
+        if matches!(param, GenericParam::Const(_)) || used_by(false, param) || !used_by(true, param)
+        {
+            return Ok(());
+        }
+        Err(syn::Error::new_spanned(
+            param,
+            "a generic parameter used only by skipped fields is unused in the generated types",
+        ))
+    }))?;
+    Ok(())
+}
+
+/// Whether `tokens` mention the type or lifetime parameter `param`.
+fn uses_param(tokens: TokenStream2, param: &GenericParam) -> bool {
+    let mut lifetime = false;
+    tokens.into_iter().any(|token| {
+        let found = match &token {
+            TokenTree::Group(group) => uses_param(group.stream(), param),
+            TokenTree::Ident(ident) => match param {
+                GenericParam::Type(param) => !lifetime && *ident == param.ident,
+                GenericParam::Lifetime(param) => lifetime && *ident == param.lifetime.ident,
+                GenericParam::Const(_) => false,
+            },
+            TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+        };
+        lifetime = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '\'');
+        found
+    })
+}
+
+/// Collects the results, and combines all errors into one.
+pub fn collect_results<T>(
+    results: impl IntoIterator<Item = syn::Result<T>>,
+) -> syn::Result<Vec<T>> {
+    let mut items = vec![];
+    let mut error: Option<syn::Error> = None;
+    for result in results {
+        match result {
+            Ok(item) => items.push(item),
+            Err(e) => match &mut error {
+                Some(error) => error.combine(e),
+                None => error = Some(e),
+            },
+        }
+    }
+    match error {
+        Some(error) => Err(error),
+        None => Ok(items),
+    }
+}
@@ -1,111 +1,100 @@
 use proc_macro::TokenStream;
-use proc_macro2::Ident;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{
-    parse::{Parse, Parser},
-    parse_macro_input, parse_str, Data, DeriveInput, Expr, FieldValue, Fields, Type,
-};
+use syn::{parse_macro_input, Data, DeriveInput};
 
-#[proc_macro_derive(FallbackSpec)]
+mod fields;
+mod options;
+mod partial;
+mod spec;
+mod traits;
+mod variant;
+
+use fields::{check_skipped_generics, collect_results, FallbackFields};
+use options::FallbackOptions;
+use partial::fallback_partial;
+use spec::{fallback_chain_spec, fallback_spec};
+use variant::{fallback_chain_variants, fallback_variants, FallbackVariant};
+
+#[proc_macro_derive(FallbackSpec, attributes(fallback))]
 pub fn derive_fallback_spec(input: TokenStream) -> TokenStream {
     let struct_input = parse_macro_input!(input as DeriveInput);
+    derive(struct_input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn derive(struct_input: DeriveInput) -> syn::Result<TokenStream2> {
     let struct_name = struct_input.ident;
     let vis = struct_input.vis;
-    let (fallback_data_declare, data_idents, base_data_idents, some_exact, none_exact, construct) =
-        match struct_input.data {
-            Data::Struct(data) => match data.fields {
-                Fields::Named(fields) => {
-                    let fields = fields.named.into_iter().collect::<Vec<_>>();
-                    let fallback_data_declare = fields
-                        .iter()
-                        .map(|field| {
-                            let mut field = field.clone();
-                            let ty = field.ty.clone();
-                            field.ty = Type::parse
-                                .parse2(quote! {::fallback::Fallback<#ty>})
-                                .unwrap();
-                            field
-                        })
-                        .collect::<Vec<_>>();
-                    let data_idents = fields
-                        .iter()
-                        .map(|field| field.ident.clone().unwrap())
-                        .collect::<Vec<_>>();
-                    let base_data_idents = fields
-                        .iter()
-                        .map(|field| {
-                            parse_str::<Ident>(&format!("base_{}", field.ident.clone().unwrap()))
-                                .expect("Parse base idents failed")
-                        })
-                        .collect::<Vec<_>>();
-                    let some_exact = fields
-                        .iter()
-                        .map(|field| {
-                            parse_str::<Expr>(&format!(
-                                "Some(data.{})",
-                                field.ident.clone().unwrap()
-                            ))
-                            .expect("Parse some exact failed")
-                        })
-                        .collect::<Vec<_>>();
-                    let none_exact =
-                        std::iter::repeat(parse_str::<Expr>("None").expect("Parse None failed"))
-                            .take(fields.len())
-                            .collect::<Vec<_>>();
-                    let construct = fields
-                        .iter()
-                        .map(|field| {
-                            let ident = field.ident.clone().unwrap();
-                            let base_ident =
-                                Ident::new(&format!("base_{}", ident.clone()), ident.span());
-                            FieldValue::parse
-                                .parse2(
-                                    quote! {#ident: ::fallback::Fallback::new(#ident, #base_ident)},
-                                )
-                                .expect("Parse field value failed")
-                        })
-                        .collect::<Vec<_>>();
-                    (
-                        fallback_data_declare,
-                        data_idents,
-                        base_data_idents,
-                        some_exact,
-                        none_exact,
-                        construct,
-                    )
-                }
-                _ => unimplemented!(),
-            },
-            _ => unimplemented!(),
-        };
-    let fallback_struct_name = parse_str::<Ident>(&format!("__Fallback{}", struct_name))
-        .expect("Parse fallback name failed");
-    let output = quote! {
-        #[doc(hidden)]
-        #vis struct #fallback_struct_name {
-            #(#fallback_data_declare ,)*
-        }
-
-        impl FallbackSpec for #struct_name {
-            type SpecType = #fallback_struct_name;
+    let generics = struct_input.generics;
+    let options = FallbackOptions::new(&struct_name, &vis, &struct_input.attrs)?;
+    let output = match struct_input.data {
+        Data::Struct(data) => {
+            let fields = FallbackFields::new(data.fields, &options)?;
+            check_skipped_generics(&generics, [&fields])?;
+            let spec = fallback_spec(&vis, &struct_name, &generics, &options, &fields);
+            let chain_spec = options.alloc_only(fallback_chain_spec(
+                &vis,
+                &struct_name,
+                &generics,
+                &options,
+                &fields,
+            ));
+            let partial = options.partial.as_ref().map(|partial_name| {
+                fallback_partial(
+                    &vis,
+                    &struct_name,
+                    &generics,
+                    partial_name,
+                    &options,
+                    &fields,
+                )
+            });
+            quote! {
+                #spec
+                #chain_spec
+                #partial
+            }
         }
-
-        impl From<::fallback::Fallback<#struct_name>> for #fallback_struct_name {
-            fn from(data: ::fallback::Fallback<#struct_name>) -> Self {
-                let (data, base_data) = data.unzip();
-                let (#(#data_idents ,)*) = match data {
-                    Some(data) => (#(#some_exact ,)*),
-                    None => (#(#none_exact ,)*),
-                };
-                let (#(#base_data_idents ,)*) = match base_data {
-                    Some(data) => (#(#some_exact ,)*),
-                    None => (#(#none_exact ,)*),
-                };
-                Self {
-                    #(#construct ,)*
-                }
+        Data::Enum(data) => {
+            if let Some(partial_name) = &options.partial {
+                return Err(syn::Error::new(
+                    partial_name.span(),
+                    "partial types are not supported on enums",
+                ));
+            }
+            if data.variants.is_empty() {
+                return Err(syn::Error::new(
+                    struct_name.span(),
+                    "FallbackSpec cannot be derived for enums without variants",
+                ));
             }
+            let variants = collect_results(
+                data.variants
+                    .into_iter()
+                    .map(|variant| FallbackVariant::new(variant, &options)),
+            )?;
+            check_skipped_generics(&generics, variants.iter().map(FallbackVariant::fields))?;
+            let spec = fallback_variants(&vis, &struct_name, &generics, &options, &variants);
+            let chain_spec = options.alloc_only(fallback_chain_variants(
+                &vis,
+                &struct_name,
+                &generics,
+                &options,
+                &variants,
+            ));
+            quote! {
+                #spec
+                #chain_spec
+            }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "FallbackSpec cannot be derived for unions",
+            ))
         }
     };
-    TokenStream::from(output)
+    Ok(output)
 }
@@ -0,0 +1,186 @@
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_quote, spanned::Spanned, Attribute, LitStr, Path, Visibility};
+
+/// The `#[fallback(...)]` attributes of the deriving type.
+pub struct FallbackOptions {
+    pub partial: Option<Ident>,
+    pub serde: bool,
+    pub on_conflict: bool,
+    pub krate: Path,
+    pub spec: Option<Ident>,
+    pub chain_spec: Option<Ident>,
+    pub spec_vis: Option<Visibility>,
+    pub spec_docs: bool,
+    pub spec_derive: Vec<Path>,
+}
+
+impl FallbackOptions {
+    pub fn new(name: &Ident, vis: &Visibility, attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut partial = None;
+        let mut serde = false;
+        let mut on_conflict = false;
+        let mut krate = parse_quote!(::fallback);
+        let mut spec = None;
+        let mut chain_spec = None;
+        let mut spec_vis = None;
+        let mut spec_docs = false;
+        let mut spec_derive = vec![];
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("fallback")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("partial") {
+                    partial = Some(
+                        if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+                            format_ident!("Partial{}", name, span = meta.path.span())
+                        } else {
+                            meta.value()?.parse::<LitStr>()?.parse::<Ident>()?
+                        },
+                    );
+                    Ok(())
+                } else if meta.path.is_ident("serde") {
+                    serde = true;
+                    Ok(())
+                } else if meta.path.is_ident("on_conflict") {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    on_conflict = match value.value().as_str() {
+                        "data" => false,
+                        "conflict" => true,
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "expected `data` or `conflict`",
+                            ))
+                        }
+                    };
+                    Ok(())
+                } else if meta.path.is_ident("crate") {
+                    krate = meta.value()?.parse::<LitStr>()?.parse::<Path>()?;
+                    Ok(())
+                } else if meta.path.is_ident("spec") {
+                    spec = Some(meta.value()?.parse::<LitStr>()?.parse::<Ident>()?);
+                    Ok(())
+                } else if meta.path.is_ident("chain_spec") {
+                    chain_spec = Some(meta.value()?.parse::<LitStr>()?.parse::<Ident>()?);
+                    Ok(())
+                } else if meta.path.is_ident("spec_vis") {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    let new_vis = value.parse::<Visibility>()?;
+                    // The `FallbackSpec` impl of the deriving type names the spec types.
+                    if visibility_rank(&new_vis) < visibility_rank(vis) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "`spec_vis` cannot be narrower than the visibility of the deriving type",
+                        ));
+                    }
+                    spec_vis = Some(new_vis);
+                    Ok(())
+                } else if meta.path.is_ident("spec_docs") {
+                    spec_docs = true;
+                    Ok(())
+                } else if meta.path.is_ident("spec_derive") {
+                    meta.parse_nested_meta(|meta| {
+                        spec_derive.push(meta.path);
+                        Ok(())
+                    })
+                } else {
+                    Err(meta.error("unsupported fallback attribute"))
+                }
+            })?;
+        }
+        Ok(Self {
+            partial,
+            serde,
+            on_conflict,
+            krate,
+            spec,
+            chain_spec,
+            spec_vis,
+            spec_docs,
+            spec_derive,
+        })
+    }
+
+    /// The name of the spec type, `__Fallback{Name}` by default.
+    pub fn spec_name(&self, name: &Ident) -> Ident {
+        self.spec
+            .clone()
+            .unwrap_or_else(|| format_ident!("__Fallback{}", name))
+    }
+
+    /// The name of the chain spec type, `__FallbackChain{Name}` by default.
+    pub fn chain_spec_name(&self, name: &Ident) -> Ident {
+        self.chain_spec
+            .clone()
+            .unwrap_or_else(|| format_ident!("__FallbackChain{}", name))
+    }
+
+    /// The visibility of the spec types, the same as the deriving type by default.
+    pub fn spec_vis<'a>(&'a self, vis: &'a Visibility) -> &'a Visibility {
+        self.spec_vis.as_ref().unwrap_or(vis)
+    }
+
+    /// The attributes of a spec type, which is hidden unless it is named,
+    /// with the extra derives.
+    pub fn spec_attrs(&self, named: bool, doc: String) -> TokenStream2 {
+        let doc = if named {
+            quote! {#[doc = #doc]}
+        } else {
+            quote! {#[doc(hidden)]}
+        };
+        let derives = &self.spec_derive;
+        let derive = (!derives.is_empty()).then(|| quote! {#[derive(#(#derives),*)]});
+        let serde_derive = self.serde_derive();
+        quote! {
+            #doc
+            #derive
+            #serde_derive
+        }
+    }
+
+    /// Derives `Serialize` and `Deserialize` for a generated type if `#[fallback(serde)]` is set.
+    pub fn serde_derive(&self) -> Option<TokenStream2> {
+        let krate = &self.krate;
+        self.serde.then(|| {
+            let serde_crate = quote!(#krate::__private::serde)
+                .to_string()
+                .replace(' ', "");
+            quote! {
+                #[derive(
+                    #krate::__private::serde::Serialize,
+                    #krate::__private::serde::Deserialize,
+                )]
+                #[serde(crate = #serde_crate)]
+            }
+        })
+    }
+
+    /// Keeps `items` only when the `alloc` feature of the crate is enabled,
+    /// as they need allocation.
+    pub fn alloc_only(&self, items: TokenStream2) -> TokenStream2 {
+        let krate = &self.krate;
+        quote! {
+            #krate::__private::alloc_only! {
+                #items
+            }
+        }
+    }
+
+    /// Keeps the attributes which are valid on a generated item.
+    pub fn retain_attrs(&self, attrs: &mut Vec<Attribute>) {
+        attrs.retain(|attr| {
+            !attr.path().is_ident("fallback") && (self.serde || !attr.path().is_ident("serde"))
+        });
+    }
+}
+
+/// Ranks a visibility from the narrowest to the widest.
+/// The paths of `pub(in path)` are not compared, and rank the same as `pub(super)`.
+fn visibility_rank(vis: &Visibility) -> u8 {
+    match vis {
+        Visibility::Public(_) => 3,
+        Visibility::Restricted(restricted) if restricted.path.is_ident("crate") => 2,
+        Visibility::Restricted(restricted) if restricted.path.is_ident("self") => 0,
+        Visibility::Restricted(_) => 1,
+        Visibility::Inherited => 0,
+    }
+}
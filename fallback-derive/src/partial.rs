@@ -0,0 +1,226 @@
+use crate::{
+    fields::{FallbackFields, FieldMode},
+    options::FallbackOptions,
+    traits::{spec_traits, SpecVariant},
+};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Generics, Visibility};
+
+pub fn fallback_partial(
+    vis: &Visibility,
+    struct_name: &Ident,
+    generics: &Generics,
+    partial_name: &Ident,
+    options: &FallbackOptions,
+    fields: &FallbackFields,
+) -> TokenStream2 {
+    let krate = &options.krate;
+    let partial_data_declare = fields
+        .iter()
+        .map(|field| {
+            field.declare(
+                quote! {::core::option::Option},
+                quote! {#krate::FallbackPartial},
+                quote! {Partial},
+                true,
+            )
+        })
+        .collect::<Vec<_>>();
+    let data_members = fields.members();
+    let spec_members = fields.spec_members();
+    let indices = fields.indices();
+    let none_exact = std::iter::repeat_n(quote! {::core::option::Option::None}, fields.len());
+    let from_exact = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            if field.nested {
+                quote! {::core::convert::From::from(data.#member)}
+            } else {
+                quote! {::core::option::Option::Some(data.#member)}
+            }
+        })
+        .collect::<Vec<_>>();
+    let present = |base: bool| {
+        fields
+            .iter()
+            .map(|field| {
+                let member = &field.spec_member;
+                let name = field.name();
+                if field.nested {
+                    let method = if base {
+                        quote! {present_base_fields}
+                    } else {
+                        quote! {present_fields}
+                    };
+                    quote! {
+                        for field in #krate::Partial::#method(&self.#member) {
+                            present.push(#krate::__private::join_field(#name, &field));
+                        }
+                    }
+                } else if (field.mode == FieldMode::BaseOnly) != base {
+                    quote! {}
+                } else {
+                    let present = match &field.predicate {
+                        Some(predicate) => quote! {self.#member.as_ref().is_some_and(#predicate)},
+                        None => quote! {self.#member.is_some()},
+                    };
+                    quote! {
+                        if #present {
+                            present.push(::core::convert::From::from(#name));
+                        }
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let present_base = present(true);
+    let present = present(false);
+    let or_exact = fields
+        .iter()
+        .map(|field| {
+            let member = &field.spec_member;
+            if field.nested {
+                quote! {#krate::Partial::or(self.#member, base.#member)}
+            } else if field.mode == FieldMode::BaseOnly {
+                let base = field.filter(quote! {base.#member});
+                quote! {#base.or(self.#member)}
+            } else {
+                let data = field.filter(quote! {self.#member});
+                match &field.merge {
+                    Some(merge) => {
+                        quote! {#krate::Fallback::new(#data, base.#member).merge_with(#merge)}
+                    }
+                    None => quote! {#data.or(base.#member)},
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let spec_exact = fields
+        .iter()
+        .zip(&indices)
+        .map(|(field, index)| {
+            let member = &field.spec_member;
+            if field.nested {
+                quote! {#krate::Partial::spec(self.#member, base.#index)}
+            } else if field.mode == FieldMode::BaseOnly {
+                let data = field.filter(quote! {self.#member});
+                let base = field.filter(quote! {base.#index});
+                quote! {#krate::Fallback::new(::core::option::Option::None, #base.or(#data))}
+            } else {
+                field.fallback(krate, quote! {self.#member}, quote! {base.#index})
+            }
+        })
+        .collect::<Vec<_>>();
+    let fallback_struct_name = options.spec_name(struct_name);
+    let doc = format!(
+        "The partial type of [`{}`], where every field is optional.",
+        struct_name
+    );
+    let serde_derive = options.serde_derive();
+    let serde_default = options.serde.then(|| quote! {#[serde(default)]});
+    let declare = fields.declare(vis, partial_name, generics, partial_data_declare);
+    let field_types = fields
+        .iter()
+        .map(|field| {
+            field.spec_ty(
+                quote! {::core::option::Option},
+                quote! {#krate::FallbackPartial},
+                quote! {Partial},
+            )
+        })
+        .collect::<Vec<_>>();
+    let traits = spec_traits(
+        partial_name,
+        generics,
+        &[],
+        &[SpecVariant {
+            path: quote! {Self},
+            name: partial_name.to_string(),
+            fields,
+        }],
+        &field_types,
+        false,
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let allow_unused = fields.allow_unused();
+    let present_fields = options.alloc_only(quote! {
+        fn present_fields(&self) -> #krate::__private::Vec<#krate::__private::String> {
+            let mut present = #krate::__private::Vec::new();
+            #(#present)*
+            present
+        }
+
+        fn present_base_fields(&self) -> #krate::__private::Vec<#krate::__private::String> {
+            let mut present = #krate::__private::Vec::new();
+            #(#present_base)*
+            present
+        }
+    });
+    quote! {
+        #[doc = #doc]
+        #serde_derive
+        #serde_default
+        #declare
+
+        #traits
+
+        impl #impl_generics ::core::default::Default for #partial_name #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#spec_members: ::core::default::Default::default() ,)*
+                }
+            }
+        }
+
+        #allow_unused
+        impl #impl_generics ::core::convert::From<#struct_name #ty_generics>
+            for #partial_name #ty_generics
+        #where_clause
+        {
+            fn from(data: #struct_name #ty_generics) -> Self {
+                Self {
+                    #(#spec_members: #from_exact ,)*
+                }
+            }
+        }
+
+        #allow_unused
+        impl #impl_generics #krate::Partial for #partial_name #ty_generics #where_clause {
+            type Full = #struct_name #ty_generics;
+
+            fn or(self, base: Self) -> Self {
+                Self {
+                    #(#spec_members: #or_exact ,)*
+                }
+            }
+
+            fn spec(
+                self,
+                base: ::core::option::Option<#struct_name #ty_generics>,
+            ) -> #fallback_struct_name #ty_generics {
+                let base = match base {
+                    ::core::option::Option::Some(data) => (#(::core::option::Option::Some(data.#data_members) ,)*),
+                    ::core::option::Option::None => (#(#none_exact ,)*),
+                };
+                #fallback_struct_name {
+                    #(#spec_members: #spec_exact ,)*
+                }
+            }
+
+            fn resolve(
+                self,
+                base: ::core::option::Option<#struct_name #ty_generics>,
+            ) -> ::core::result::Result<#struct_name #ty_generics, #krate::MissingFields> {
+                #krate::Partial::spec(self, base).resolve()
+            }
+
+            #present_fields
+        }
+
+        impl #impl_generics #krate::FallbackPartial for #struct_name #ty_generics #where_clause {
+            type Partial = #partial_name #ty_generics;
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{
+    fields::FallbackFields,
+    options::FallbackOptions,
+    traits::{spec_traits, SpecVariant},
+};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Generics, Path, Type, Visibility};
+
+pub fn fallback_spec(
+    vis: &Visibility,
+    struct_name: &Ident,
+    generics: &Generics,
+    options: &FallbackOptions,
+    fields: &FallbackFields,
+) -> TokenStream2 {
+    let krate = &options.krate;
+    let fallback_data_declare = fields
+        .iter()
+        .map(|field| {
+            field.declare(
+                quote! {#krate::Fallback},
+                quote! {#krate::FallbackSpec},
+                quote! {SpecType},
+                options.spec_docs,
+            )
+        })
+        .collect::<Vec<_>>();
+    let data_members = fields.members();
+    let spec_members = fields.spec_members();
+    let none_exact = std::iter::repeat_n(quote! {::core::option::Option::None}, fields.len())
+        .collect::<Vec<_>>();
+    let fallbacks = fields
+        .iter()
+        .zip(fields.indices())
+        .map(|(field, index)| {
+            field.fallback(krate, quote! {data.#index}, quote! {base_data.#index})
+        })
+        .collect::<Vec<_>>();
+    let provenance = fields
+        .iter()
+        .map(|field| {
+            let member = &field.spec_member;
+            field.provenance(krate, quote! {self.#member}, quote! {source})
+        })
+        .collect::<Vec<_>>();
+    let entries = fields.iter().filter_map(|field| field.entry(krate));
+    let fallback_struct_name = options.spec_name(struct_name);
+    let declare = fields.declare(
+        options.spec_vis(vis),
+        &fallback_struct_name,
+        generics,
+        fallback_data_declare,
+    );
+    let resolve = resolve_spec(krate, struct_name, &fallback_struct_name, generics, fields);
+    let traits = struct_traits(
+        &fallback_struct_name,
+        generics,
+        options,
+        fields,
+        fields
+            .iter()
+            .map(|field| {
+                field.spec_ty(
+                    quote! {#krate::Fallback},
+                    quote! {#krate::FallbackSpec},
+                    quote! {SpecType},
+                )
+            })
+            .collect(),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let attrs = options.spec_attrs(
+        options.spec.is_some(),
+        format!("The specialized fallback type of [`{}`].", struct_name),
+    );
+    let allow_unused = fields.allow_unused();
+    let provenance = options.alloc_only(quote! {
+        #allow_unused
+        impl #impl_generics #fallback_struct_name #ty_generics #where_clause {
+            /// Reports the layer which supplies each field.
+            pub fn provenance(
+                &self,
+            ) -> #krate::__private::Vec<(#krate::__private::String, ::core::option::Option<#krate::Source>)> {
+                let mut provenance = #krate::__private::Vec::new();
+                #(#provenance)*
+                provenance
+            }
+        }
+    });
+    let serde_default = options.serde.then(|| quote! {#[serde(default)]});
+    quote! {
+        #attrs
+        #serde_default
+        #declare
+
+        #traits
+
+        impl #impl_generics #krate::FallbackSpec for #struct_name #ty_generics #where_clause {
+            type SpecType = #fallback_struct_name #ty_generics;
+        }
+
+        #allow_unused
+        impl #impl_generics ::core::convert::From<#krate::Fallback<#struct_name #ty_generics>>
+            for #fallback_struct_name #ty_generics
+        #where_clause
+        {
+            fn from(data: #krate::Fallback<#struct_name #ty_generics>) -> Self {
+                let (data, base_data) = data.unzip();
+                let data = match data {
+                    ::core::option::Option::Some(data) => (#(::core::option::Option::Some(data.#data_members) ,)*),
+                    ::core::option::Option::None => (#(#none_exact ,)*),
+                };
+                let base_data = match base_data {
+                    ::core::option::Option::Some(data) => (#(::core::option::Option::Some(data.#data_members) ,)*),
+                    ::core::option::Option::None => (#(#none_exact ,)*),
+                };
+                Self {
+                    #(#spec_members: #fallbacks ,)*
+                }
+            }
+        }
+
+        #provenance
+
+        #[allow(dead_code)]
+        impl #impl_generics #fallback_struct_name #ty_generics #where_clause {
+            #(#entries)*
+        }
+
+        #resolve
+    }
+}
+
+pub fn fallback_chain_spec(
+    vis: &Visibility,
+    struct_name: &Ident,
+    generics: &Generics,
+    options: &FallbackOptions,
+    fields: &FallbackFields,
+) -> TokenStream2 {
+    let krate = &options.krate;
+    let fallback_data_declare = fields
+        .iter()
+        .map(|field| {
+            field.declare(
+                quote! {#krate::FallbackChain},
+                quote! {#krate::FallbackChainSpec},
+                quote! {ChainSpecType},
+                options.spec_docs,
+            )
+        })
+        .collect::<Vec<_>>();
+    let data_members = fields.members();
+    let spec_members = fields.spec_members();
+    let indices = fields.indices();
+    let vec_new = std::iter::repeat_n(quote! {#krate::__private::Vec::new()}, fields.len());
+    let fallbacks = fields
+        .iter()
+        .zip(&indices)
+        .map(|(field, index)| field.fallback_chain(krate, quote! {layers.#index}))
+        .collect::<Vec<_>>();
+    let provenance = fields
+        .iter()
+        .map(|field| {
+            let member = &field.spec_member;
+            field.provenance(krate, quote! {self.#member}, quote! {layer})
+        })
+        .collect::<Vec<_>>();
+    let fallback_struct_name = options.chain_spec_name(struct_name);
+    let declare = fields.declare(
+        options.spec_vis(vis),
+        &fallback_struct_name,
+        generics,
+        fallback_data_declare,
+    );
+    let resolve = resolve_spec(krate, struct_name, &fallback_struct_name, generics, fields);
+    let traits = struct_traits(
+        &fallback_struct_name,
+        generics,
+        options,
+        fields,
+        fields
+            .iter()
+            .map(|field| {
+                field.spec_ty(
+                    quote! {#krate::FallbackChain},
+                    quote! {#krate::FallbackChainSpec},
+                    quote! {ChainSpecType},
+                )
+            })
+            .collect(),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let attrs = options.spec_attrs(
+        options.chain_spec.is_some(),
+        format!(
+            "The specialized fallback chain type of [`{}`].",
+            struct_name
+        ),
+    );
+    let serde_default = options.serde.then(|| quote! {#[serde(default)]});
+    let allow_unused = fields.allow_unused();
+    quote! {
+        #attrs
+        #serde_default
+        #declare
+
+        #traits
+
+        impl #impl_generics #krate::FallbackChainSpec for #struct_name #ty_generics #where_clause {
+            type ChainSpecType = #fallback_struct_name #ty_generics;
+        }
+
+        #allow_unused
+        impl #impl_generics ::core::convert::From<#krate::FallbackChain<#struct_name #ty_generics>>
+            for #fallback_struct_name #ty_generics
+        #where_clause
+        {
+            fn from(data: #krate::FallbackChain<#struct_name #ty_generics>) -> Self {
+                let mut layers = (#(#vec_new ,)*);
+                for data in data.into_layers() {
+                    match data {
+                        ::core::option::Option::Some(data) => {
+                            #(layers.#indices.push(::core::option::Option::Some(data.#data_members));)*
+                        }
+                        ::core::option::Option::None => {
+                            #(layers.#indices.push(::core::option::Option::None);)*
+                        }
+                    }
+                }
+                Self {
+                    #(#spec_members: #fallbacks ,)*
+                }
+            }
+        }
+
+        #allow_unused
+        impl #impl_generics #fallback_struct_name #ty_generics #where_clause {
+            /// Reports the index of the layer which supplies each field.
+            pub fn provenance(&self) -> #krate::__private::Vec<(#krate::__private::String, ::core::option::Option<usize>)> {
+                let mut provenance = #krate::__private::Vec::new();
+                #(#provenance)*
+                provenance
+            }
+        }
+
+        #resolve
+    }
+}
+
+/// Implements the standard traits for a generated spec struct.
+fn struct_traits(
+    name: &Ident,
+    generics: &Generics,
+    options: &FallbackOptions,
+    fields: &FallbackFields,
+    field_types: Vec<Type>,
+) -> TokenStream2 {
+    spec_traits(
+        name,
+        generics,
+        &options.spec_derive,
+        &[SpecVariant {
+            path: quote! {Self},
+            name: name.to_string(),
+            fields,
+        }],
+        &field_types,
+        true,
+    )
+}
+
+fn resolve_spec(
+    krate: &Path,
+    struct_name: &Ident,
+    fallback_struct_name: &Ident,
+    generics: &Generics,
+    fields: &FallbackFields,
+) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let values = fields
+        .iter()
+        .map(|field| {
+            let member = &field.spec_member;
+            quote! {self.#member}
+        })
+        .collect();
+    let resolve = resolve_fields(krate, quote! {#struct_name}, fields, values);
+    let allow_unused = fields.allow_unused();
+    quote! {
+        #allow_unused
+        impl #impl_generics #fallback_struct_name #ty_generics #where_clause {
+            /// Fallbacks every field, and collects them into the original struct.
+            pub fn resolve(
+                self,
+            ) -> ::core::result::Result<#struct_name #ty_generics, #krate::MissingFields> {
+                #resolve
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<#fallback_struct_name #ty_generics>
+            for #struct_name #ty_generics
+        #where_clause
+        {
+            type Error = #krate::MissingFields;
+
+            fn try_from(
+                data: #fallback_struct_name #ty_generics,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                data.resolve()
+            }
+        }
+    }
+}
+
+/// Fallbacks the wrapped fields `values`, and constructs them with `path`,
+/// together with the default values of the skipped fields,
+/// or returns all missing fields.
+pub fn resolve_fields(
+    krate: &Path,
+    path: TokenStream2,
+    fields: &FallbackFields,
+    values: Vec<TokenStream2>,
+) -> TokenStream2 {
+    let data_members = fields.members();
+    let skipped_members = fields.skipped_members();
+    let indices = fields.indices();
+    let resolve = fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| field.resolve(value))
+        .collect::<Vec<_>>();
+    quote! {
+        let mut missing = <#krate::MissingFields as ::core::default::Default>::default();
+        let data = (#(#resolve ,)*);
+        if missing.is_empty() {
+            ::core::result::Result::Ok(#path {
+                #(#data_members: data.#indices.unwrap() ,)*
+                #(#skipped_members: ::core::default::Default::default() ,)*
+            })
+        } else {
+            ::core::result::Result::Err(missing)
+        }
+    }
+}
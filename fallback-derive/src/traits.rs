@@ -0,0 +1,260 @@
+use crate::fields::{FallbackFields, FieldsStyle};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_quote, Generics, Path, Type, WherePredicate};
+
+/// A generated struct, or a variant of a generated enum.
+pub struct SpecVariant<'a> {
+    /// The path to construct or destructure it, `Self` or `Self::Variant`.
+    pub path: TokenStream2,
+    /// The name shown by `Debug`.
+    pub name: String,
+    pub fields: &'a FallbackFields,
+}
+
+/// Implements `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash`,
+/// and `Default` if `default` is set, for a generated type.
+/// The variants are compared by their order first, and then the fields in order, as `#[derive]` does.
+///
+/// Each impl is bounded on the field types, so that it only applies
+/// when the fields implement the trait. The traits in `derived` are skipped,
+/// as they are derived by `#[fallback(spec_derive(...))]`.
+pub fn spec_traits(
+    name: &Ident,
+    generics: &Generics,
+    derived: &[Path],
+    variants: &[SpecVariant],
+    field_types: &[Type],
+    default: bool,
+) -> TokenStream2 {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let impl_trait = |trait_name: &str, trait_path: TokenStream2, body: TokenStream2| {
+        if derived
+            .iter()
+            .any(|path| path.segments.last().is_some_and(|s| s.ident == trait_name))
+        {
+            return quote! {};
+        }
+        let mut generics = generics.clone();
+        let where_clause = generics.make_where_clause();
+        for ty in field_types {
+            // The impls are bounded on the field types rather than the type parameters
+            // as `#[derive]` does, so that a field like `f64` only leaves out `Eq`.
+            // A bound on a concrete type which doesn't implement the trait is rejected
+            // as trivially false without the unstable `trivial_bounds` feature,
+            // see https://github.com/rust-lang/rust/issues/48214, while a higher-ranked
+            // bound is only checked where the impl is used, so the impl just doesn't apply.
+            let predicate: WherePredicate = parse_quote! {for<'__fallback> #ty: #trait_path};
+            where_clause.predicates.push(predicate);
+        }
+        quote! {
+            #[allow(unused_variables)]
+            impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+                #body
+            }
+        }
+    };
+    let multiple = variants.len() > 1;
+
+    let debug = variants.iter().map(|variant| {
+        let path = &variant.path;
+        let name = &variant.name;
+        let bindings = variant.fields.bindings();
+        let pattern = variant.fields.spec_pattern(path.clone());
+        let names = variant.fields.iter().map(|field| field.name());
+        let body = match variant.fields.style() {
+            FieldsStyle::Named => quote! {
+                f.debug_struct(#name) #(.field(#names, #bindings))* .finish()
+            },
+            FieldsStyle::Unnamed => quote! {
+                f.debug_tuple(#name) #(.field(#bindings))* .finish()
+            },
+            FieldsStyle::Unit => quote! {f.write_str(#name)},
+        };
+        quote! {#pattern => #body,}
+    });
+    let debug = impl_trait(
+        "Debug",
+        quote! {::core::fmt::Debug},
+        quote! {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#debug)*
+                }
+            }
+        },
+    );
+
+    let clone = variants.iter().map(|variant| {
+        let path = &variant.path;
+        let members = variant.fields.spec_members();
+        let bindings = variant.fields.bindings();
+        let pattern = variant.fields.spec_pattern(path.clone());
+        quote! {
+            #pattern => #path {
+                #(#members: ::core::clone::Clone::clone(#bindings) ,)*
+            },
+        }
+    });
+    let clone = impl_trait(
+        "Clone",
+        quote! {::core::clone::Clone},
+        quote! {
+            fn clone(&self) -> Self {
+                match self {
+                    #(#clone)*
+                }
+            }
+        },
+    );
+    let copy = impl_trait("Copy", quote! {::core::marker::Copy}, quote! {});
+
+    let eq = variants.iter().map(|variant| {
+        let path = &variant.path;
+        let bindings = variant.fields.bindings();
+        let others = variant.fields.prefixed_bindings("other");
+        let pattern = variant.fields.spec_pattern(path.clone());
+        let other_pattern = variant.fields.spec_pattern_with(path.clone(), &others);
+        quote! {
+            (#pattern, #other_pattern) => true #(&& #bindings == #others)*,
+        }
+    });
+    let ne = multiple.then(|| quote! {_ => false,});
+    let partial_eq = impl_trait(
+        "PartialEq",
+        quote! {::core::cmp::PartialEq},
+        quote! {
+            fn eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    #(#eq)*
+                    #ne
+                }
+            }
+        },
+    );
+    let eq = impl_trait("Eq", quote! {::core::cmp::Eq}, quote! {});
+
+    let index = |value: TokenStream2| {
+        let arms = variants.iter().enumerate().map(|(i, variant)| {
+            let path = &variant.path;
+            quote! {#path { .. } => #i,}
+        });
+        quote! {
+            match #value {
+                #(#arms)*
+            }
+        }
+    };
+    let compare = |cmp: TokenStream2, equal: TokenStream2| {
+        let arms = variants.iter().map(|variant| {
+            let path = &variant.path;
+            let bindings = variant.fields.bindings();
+            let others = variant.fields.prefixed_bindings("other");
+            let pattern = variant.fields.spec_pattern(path.clone());
+            let other_pattern = variant.fields.spec_pattern_with(path.clone(), &others);
+            quote! {
+                (#pattern, #other_pattern) => {
+                    #(
+                        match #cmp(#bindings, #others) {
+                            #equal => {}
+                            ordering => return ordering,
+                        }
+                    )*
+                    #equal
+                }
+            }
+        });
+        let by_index = multiple.then(|| {
+            let self_index = index(quote! {self});
+            let other_index = index(quote! {other});
+            quote! {_ => #cmp(&#self_index, &#other_index),}
+        });
+        quote! {
+            match (self, other) {
+                #(#arms)*
+                #by_index
+            }
+        }
+    };
+
+    let partial_cmp = compare(
+        quote! {::core::cmp::PartialOrd::partial_cmp},
+        quote! {::core::option::Option::Some(::core::cmp::Ordering::Equal)},
+    );
+    let partial_ord = impl_trait(
+        "PartialOrd",
+        quote! {::core::cmp::PartialOrd},
+        quote! {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                #partial_cmp
+            }
+        },
+    );
+    let cmp = compare(
+        quote! {::core::cmp::Ord::cmp},
+        quote! {::core::cmp::Ordering::Equal},
+    );
+    let ord = impl_trait(
+        "Ord",
+        quote! {::core::cmp::Ord},
+        quote! {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                #cmp
+            }
+        },
+    );
+
+    let hash = variants.iter().map(|variant| {
+        let bindings = variant.fields.bindings();
+        let pattern = variant.fields.spec_pattern(variant.path.clone());
+        quote! {
+            #pattern => {
+                #(::core::hash::Hash::hash(#bindings, state);)*
+            }
+        }
+    });
+    let discriminant = multiple.then(|| {
+        quote! {::core::hash::Hash::hash(&::core::mem::discriminant(self), state);}
+    });
+    let hash = impl_trait(
+        "Hash",
+        quote! {::core::hash::Hash},
+        quote! {
+            fn hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+                #discriminant
+                match self {
+                    #(#hash)*
+                }
+            }
+        },
+    );
+
+    let default = default.then(|| {
+        let variant = &variants[0];
+        let path = &variant.path;
+        let members = variant.fields.spec_members();
+        impl_trait(
+            "Default",
+            quote! {::core::default::Default},
+            quote! {
+                fn default() -> Self {
+                    #path {
+                        #(#members: ::core::default::Default::default() ,)*
+                    }
+                }
+            },
+        )
+    });
+
+    quote! {
+        #debug
+        #clone
+        #copy
+        #partial_eq
+        #eq
+        #partial_ord
+        #ord
+        #hash
+        #default
+    }
+}
@@ -0,0 +1,464 @@
+use crate::{
+    fields::FallbackFields,
+    options::FallbackOptions,
+    spec::resolve_fields,
+    traits::{spec_traits, SpecVariant},
+};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Attribute, Generics, Path, Variant, Visibility};
+
+/// A variant of the deriving enum.
+pub struct FallbackVariant {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    fields: FallbackFields,
+}
+
+impl FallbackVariant {
+    pub fn new(mut variant: Variant, options: &FallbackOptions) -> syn::Result<Self> {
+        if let Some(attr) = variant
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("fallback"))
+        {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "fallback attributes are not supported on variants",
+            ));
+        }
+        variant.attrs.retain(|attr| {
+            attr.path().is_ident("doc") || (options.serde && attr.path().is_ident("serde"))
+        });
+        Ok(Self {
+            attrs: variant.attrs,
+            ident: variant.ident,
+            fields: FallbackFields::new(variant.fields, options)?,
+        })
+    }
+
+    pub fn fields(&self) -> &FallbackFields {
+        &self.fields
+    }
+
+    fn declare(
+        &self,
+        wrapper: TokenStream2,
+        spec_trait: TokenStream2,
+        spec_type: TokenStream2,
+        docs: bool,
+    ) -> TokenStream2 {
+        let attrs = self
+            .attrs
+            .iter()
+            .filter(|attr| docs || !attr.path().is_ident("doc"));
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                field.declare(wrapper.clone(), spec_trait.clone(), spec_type.clone(), docs)
+            })
+            .collect();
+        let declare = self.fields.declare_variant(&self.ident, fields);
+        quote! {
+            #(#attrs)*
+            #declare
+        }
+    }
+
+    fn spec_variant(&self) -> SpecVariant<'_> {
+        let ident = &self.ident;
+        SpecVariant {
+            path: quote! {Self::#ident},
+            name: ident.to_string(),
+            fields: &self.fields,
+        }
+    }
+
+    fn provenance(&self, krate: &Path, source: TokenStream2) -> Vec<TokenStream2> {
+        self.fields
+            .iter()
+            .zip(self.fields.bindings())
+            .map(|(field, binding)| field.provenance(krate, quote! {#binding}, source.clone()))
+            .collect()
+    }
+
+    fn resolve(&self, krate: &Path) -> TokenStream2 {
+        let ident = &self.ident;
+        let values = self
+            .fields
+            .bindings()
+            .into_iter()
+            .map(|binding| quote! {#binding})
+            .collect();
+        resolve_fields(krate, quote! {Self::#ident}, &self.fields, values)
+    }
+}
+
+/// Implements the standard traits for a generated variants enum.
+fn variants_traits(
+    name: &Ident,
+    generics: &Generics,
+    options: &FallbackOptions,
+    variants: &[FallbackVariant],
+    wrapper: TokenStream2,
+    spec_trait: TokenStream2,
+    spec_type: TokenStream2,
+) -> TokenStream2 {
+    let field_types = variants
+        .iter()
+        .flat_map(|variant| variant.fields.iter())
+        .map(|field| field.spec_ty(wrapper.clone(), spec_trait.clone(), spec_type.clone()))
+        .collect::<Vec<_>>();
+    let spec_variants = variants
+        .iter()
+        .map(FallbackVariant::spec_variant)
+        .collect::<Vec<_>>();
+    spec_traits(
+        name,
+        generics,
+        &options.spec_derive,
+        &spec_variants,
+        &field_types,
+        false,
+    )
+}
+
+pub fn fallback_variants(
+    vis: &Visibility,
+    enum_name: &Ident,
+    generics: &Generics,
+    options: &FallbackOptions,
+    variants: &[FallbackVariant],
+) -> TokenStream2 {
+    let krate = &options.krate;
+    let spec_vis = options.spec_vis(vis);
+    let declare = variants
+        .iter()
+        .map(|variant| {
+            variant.declare(
+                quote! {#krate::Fallback},
+                quote! {#krate::FallbackSpec},
+                quote! {SpecType},
+                options.spec_docs,
+            )
+        })
+        .collect::<Vec<_>>();
+    let fallback_enum_name = options.spec_name(enum_name);
+    let traits = variants_traits(
+        &fallback_enum_name,
+        generics,
+        options,
+        variants,
+        quote! {#krate::Fallback},
+        quote! {#krate::FallbackSpec},
+        quote! {SpecType},
+    );
+    let conflict = options.on_conflict.then(|| {
+        quote! {
+            let (data, base_data) = match (data, base_data) {
+                (::core::option::Option::Some(data), ::core::option::Option::Some(base))
+                    if ::core::mem::discriminant(&data) != ::core::mem::discriminant(&base) =>
+                {
+                    return #krate::VariantFallback::Conflict { data, base };
+                }
+                layers => layers,
+            };
+        }
+    });
+    let spec = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let pattern = variant.fields.pattern(quote! {Self::#ident});
+            let bindings = variant.fields.bindings();
+            let spec_members = variant.fields.spec_members();
+            let none_exact =
+                std::iter::repeat_n(quote! {::core::option::Option::None}, variant.fields.len()).collect::<Vec<_>>();
+            let fallbacks = variant
+                .fields
+                .iter()
+                .zip(variant.fields.indices())
+                .map(|(field, index)| {
+                    field.fallback(krate, quote! {data.#index}, quote! {base_data.#index})
+                })
+                .collect::<Vec<_>>();
+            quote! {
+                ::core::option::Option::Some(Self::#ident { .. }) => {
+                    let data = match data {
+                        ::core::option::Option::Some(#pattern) => (#(::core::option::Option::Some(#bindings) ,)*),
+                        _ => (#(#none_exact ,)*),
+                    };
+                    let base_data = match base_data {
+                        ::core::option::Option::Some(#pattern) => (#(::core::option::Option::Some(#bindings) ,)*),
+                        _ => (#(#none_exact ,)*),
+                    };
+                    #krate::VariantFallback::Variant(
+                        #fallback_enum_name::#ident {
+                            #(#spec_members: #fallbacks ,)*
+                        },
+                        source,
+                    )
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let provenance = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let pattern = variant
+                .fields
+                .spec_pattern(quote! {#fallback_enum_name::#ident});
+            let provenance = variant.provenance(krate, quote! {source});
+            quote! {
+                #pattern => {
+                    #(#provenance)*
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let resolve = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let pattern = variant
+                .fields
+                .spec_pattern(quote! {#fallback_enum_name::#ident});
+            let resolve = variant.resolve(krate);
+            quote! {
+                #pattern => {
+                    #resolve
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let attrs = options.spec_attrs(
+        options.spec.is_some(),
+        format!(
+            "The variants of [`{}`], with the fields wrapped in `Fallback`.",
+            enum_name
+        ),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let provenance = options.alloc_only(quote! {
+        fn provenance(
+            variants: &#fallback_enum_name #ty_generics,
+        ) -> #krate::__private::Vec<(#krate::__private::String, ::core::option::Option<#krate::Source>)> {
+            let mut provenance = #krate::__private::Vec::new();
+            match variants {
+                #(#provenance)*
+            }
+            provenance
+        }
+    });
+    quote! {
+        #attrs
+        #spec_vis enum #fallback_enum_name #generics #where_clause {
+            #(#declare ,)*
+        }
+
+        #traits
+
+        impl #impl_generics #krate::FallbackSpec for #enum_name #ty_generics #where_clause {
+            type SpecType = #krate::VariantFallback<#enum_name #ty_generics>;
+        }
+
+        #[allow(unused_variables, unused_mut)]
+        impl #impl_generics #krate::FallbackVariants for #enum_name #ty_generics #where_clause {
+            type Variants = #fallback_enum_name #ty_generics;
+
+            fn spec(data: ::core::option::Option<Self>, base_data: ::core::option::Option<Self>) -> #krate::VariantFallback<Self> {
+                #conflict
+                let source = if data.is_some() {
+                    #krate::Source::Data
+                } else {
+                    #krate::Source::Base
+                };
+                match data.as_ref().or(base_data.as_ref()) {
+                    ::core::option::Option::None => #krate::VariantFallback::None,
+                    #(#spec)*
+                }
+            }
+
+            #provenance
+
+            fn resolve(
+                variants: #fallback_enum_name #ty_generics,
+            ) -> ::core::result::Result<Self, #krate::MissingFields> {
+                match variants {
+                    #(#resolve)*
+                }
+            }
+        }
+    }
+}
+
+pub fn fallback_chain_variants(
+    vis: &Visibility,
+    enum_name: &Ident,
+    generics: &Generics,
+    options: &FallbackOptions,
+    variants: &[FallbackVariant],
+) -> TokenStream2 {
+    let krate = &options.krate;
+    let spec_vis = options.spec_vis(vis);
+    let declare = variants
+        .iter()
+        .map(|variant| {
+            variant.declare(
+                quote! {#krate::FallbackChain},
+                quote! {#krate::FallbackChainSpec},
+                quote! {ChainSpecType},
+                options.spec_docs,
+            )
+        })
+        .collect::<Vec<_>>();
+    let fallback_enum_name = options.chain_spec_name(enum_name);
+    let traits = variants_traits(
+        &fallback_enum_name,
+        generics,
+        options,
+        variants,
+        quote! {#krate::FallbackChain},
+        quote! {#krate::FallbackChainSpec},
+        quote! {ChainSpecType},
+    );
+    let conflict = options.on_conflict.then(|| {
+        quote! {
+            let first = layers[layer].as_ref().map(::core::mem::discriminant);
+            if ::core::iter::Iterator::any(&mut ::core::iter::Iterator::flatten(layers.iter()), |data| {
+                ::core::option::Option::Some(::core::mem::discriminant(data)) != first
+            }) {
+                return #krate::VariantFallbackChain::Conflict(layers);
+            }
+        }
+    });
+    let spec = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let pattern = variant.fields.pattern(quote! {Self::#ident});
+            let bindings = variant.fields.bindings();
+            let spec_members = variant.fields.spec_members();
+            let indices = variant.fields.indices();
+            let vec_new =
+                std::iter::repeat_n(quote! {#krate::__private::Vec::new()}, variant.fields.len());
+            let fallbacks = variant
+                .fields
+                .iter()
+                .zip(&indices)
+                .map(|(field, index)| field.fallback_chain(krate, quote! {fields.#index}))
+                .collect::<Vec<_>>();
+            quote! {
+                ::core::option::Option::Some(Self::#ident { .. }) => {
+                    let mut fields = (#(#vec_new ,)*);
+                    for data in layers {
+                        match data {
+                            ::core::option::Option::Some(#pattern) => {
+                                #(fields.#indices.push(::core::option::Option::Some(#bindings));)*
+                            }
+                            _ => {
+                                #(fields.#indices.push(::core::option::Option::None);)*
+                            }
+                        }
+                    }
+                    #krate::VariantFallbackChain::Variant(
+                        #fallback_enum_name::#ident {
+                            #(#spec_members: #fallbacks ,)*
+                        },
+                        layer,
+                    )
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let provenance = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let pattern = variant
+                .fields
+                .spec_pattern(quote! {#fallback_enum_name::#ident});
+            let provenance = variant.provenance(krate, quote! {layer});
+            quote! {
+                #pattern => {
+                    #(#provenance)*
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let resolve = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let pattern = variant
+                .fields
+                .spec_pattern(quote! {#fallback_enum_name::#ident});
+            let resolve = variant.resolve(krate);
+            quote! {
+                #pattern => {
+                    #resolve
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let attrs = options.spec_attrs(
+        options.chain_spec.is_some(),
+        format!(
+            "The variants of [`{}`], with the fields wrapped in `FallbackChain`.",
+            enum_name
+        ),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #attrs
+        #spec_vis enum #fallback_enum_name #generics #where_clause {
+            #(#declare ,)*
+        }
+
+        #traits
+
+        impl #impl_generics #krate::FallbackChainSpec for #enum_name #ty_generics #where_clause {
+            type ChainSpecType = #krate::VariantFallbackChain<#enum_name #ty_generics>;
+        }
+
+        #[allow(unused_variables, unused_mut)]
+        impl #impl_generics #krate::FallbackChainVariants for #enum_name #ty_generics #where_clause {
+            type ChainVariants = #fallback_enum_name #ty_generics;
+
+            fn spec(layers: #krate::__private::Vec<::core::option::Option<Self>>) -> #krate::VariantFallbackChain<Self> {
+                let layer = match ::core::iter::Iterator::position(
+                    &mut layers.iter(),
+                    ::core::option::Option::is_some,
+                ) {
+                    ::core::option::Option::Some(layer) => layer,
+                    ::core::option::Option::None => return #krate::VariantFallbackChain::None,
+                };
+                #conflict
+                match &layers[layer] {
+                    ::core::option::Option::None => #krate::VariantFallbackChain::None,
+                    #(#spec)*
+                }
+            }
+
+            fn provenance(
+                variants: &#fallback_enum_name #ty_generics,
+            ) -> #krate::__private::Vec<(#krate::__private::String, ::core::option::Option<usize>)> {
+                let mut provenance = #krate::__private::Vec::new();
+                match variants {
+                    #(#provenance)*
+                }
+                provenance
+            }
+
+            fn resolve(
+                variants: #fallback_enum_name #ty_generics,
+            ) -> ::core::result::Result<Self, #krate::MissingFields> {
+                match variants {
+                    #(#resolve)*
+                }
+            }
+        }
+    }
+}
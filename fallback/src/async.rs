@@ -0,0 +1,285 @@
+//! Fallback with layers computed by futures.
+//!
+//! The types here only rely on [`Future`], and work with any executor.
+
+use crate::Source;
+use core::{
+    future::{poll_fn, Future},
+    pin::{pin, Pin},
+    task::{ready, Context, Poll},
+};
+
+/// Stores two futures of [`Option`], and provides functionality to fallback.
+///
+/// The base future is awaited only when the data future doesn't supply a value.
+/// ```
+/// # use fallback::r#async::AsyncFallback;
+/// # async fn remote_cache() -> Option<String> { None }
+/// # async fn local_default() -> Option<String> { Some("local".to_string()) }
+/// # async fn run() {
+/// let fallback = AsyncFallback::new(remote_cache(), local_default());
+/// let value = fallback.fallback().await;
+/// assert_eq!(value, Some("local".to_string()));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncFallback<D, B> {
+    data: D,
+    base_data: B,
+}
+
+impl<T, D: Future<Output = Option<T>>, B: Future<Output = Option<T>>> AsyncFallback<D, B> {
+    /// Creates a new [`AsyncFallback`].
+    pub const fn new(data: D, base_data: B) -> Self {
+        Self { data, base_data }
+    }
+
+    /// Fallbacks the data or part of data with an async function,
+    /// and reports the layer it comes from.
+    pub async fn and_then_async_with_source<V, F: Future<Output = Option<V>>>(
+        self,
+        mut f: impl FnMut(T) -> F,
+    ) -> Option<(V, Source)> {
+        if let Some(data) = self.data.await {
+            if let Some(v) = f(data).await {
+                return Some((v, Source::Data));
+            }
+        }
+        match self.base_data.await {
+            Some(data) => f(data).await.map(|v| (v, Source::Base)),
+            None => None,
+        }
+    }
+
+    /// Fallbacks the data or part of data with an async function.
+    pub async fn and_then_async<V, F: Future<Output = Option<V>>>(
+        self,
+        f: impl FnMut(T) -> F,
+    ) -> Option<V> {
+        self.and_then_async_with_source(f).await.map(|(v, _)| v)
+    }
+
+    /// Fallbacks the data or part of data.
+    pub async fn and_then<V>(self, mut f: impl FnMut(T) -> Option<V>) -> Option<V> {
+        self.and_then_async(|data| core::future::ready(f(data)))
+            .await
+    }
+
+    /// Fallbacks the total data.
+    pub async fn fallback(self) -> Option<T> {
+        self.and_then(Some).await
+    }
+
+    /// Fallbacks the total data, and reports the layer it comes from.
+    pub async fn fallback_with_source(self) -> Option<(T, Source)> {
+        self.and_then_async_with_source(|data| core::future::ready(Some(data)))
+            .await
+    }
+
+    /// Polls both futures concurrently, while still preferring the data layer.
+    pub fn race(self) -> RacingFallback<D, B> {
+        RacingFallback {
+            data: self.data,
+            base_data: self.base_data,
+        }
+    }
+}
+
+/// An [`AsyncFallback`] which polls both futures concurrently,
+/// created by [`AsyncFallback::race`].
+///
+/// The base future is started along with the data future,
+/// but its value is used only when the data future doesn't supply a value.
+/// Both futures are pinned on the stack of the returned future, without allocation.
+#[derive(Debug)]
+pub struct RacingFallback<D, B> {
+    data: D,
+    base_data: B,
+}
+
+impl<T, D: Future<Output = Option<T>>, B: Future<Output = Option<T>>> RacingFallback<D, B> {
+    /// Fallbacks the data or part of data with an async function,
+    /// and reports the layer it comes from.
+    ///
+    /// The base future is polled while the data future and `f` are pending.
+    pub async fn and_then_async_with_source<V, F: Future<Output = Option<V>>>(
+        self,
+        mut f: impl FnMut(T) -> F,
+    ) -> Option<(V, Source)> {
+        let mut data = MaybeDone::Future(pin!(self.data));
+        let mut base_data = MaybeDone::Future(pin!(self.base_data));
+        let data = poll_fn(|cx| {
+            let _ = base_data.poll(cx);
+            ready!(data.poll(cx));
+            Poll::Ready(data.take().flatten())
+        })
+        .await;
+        if let Some(data) = data {
+            let mut future = pin!(f(data));
+            let v = poll_fn(|cx| {
+                let _ = base_data.poll(cx);
+                future.as_mut().poll(cx)
+            })
+            .await;
+            if let Some(v) = v {
+                return Some((v, Source::Data));
+            }
+        }
+        let base_data = poll_fn(|cx| {
+            ready!(base_data.poll(cx));
+            Poll::Ready(base_data.take().flatten())
+        })
+        .await;
+        match base_data {
+            Some(data) => f(data).await.map(|v| (v, Source::Base)),
+            None => None,
+        }
+    }
+
+    /// Fallbacks the data or part of data with an async function.
+    pub async fn and_then_async<V, F: Future<Output = Option<V>>>(
+        self,
+        f: impl FnMut(T) -> F,
+    ) -> Option<V> {
+        self.and_then_async_with_source(f).await.map(|(v, _)| v)
+    }
+
+    /// Fallbacks the data or part of data.
+    pub async fn and_then<V>(self, mut f: impl FnMut(T) -> Option<V>) -> Option<V> {
+        self.and_then_async(|data| core::future::ready(f(data)))
+            .await
+    }
+
+    /// Fallbacks the total data.
+    pub async fn fallback(self) -> Option<T> {
+        self.and_then(Some).await
+    }
+
+    /// Fallbacks the total data, and reports the layer it comes from.
+    pub async fn fallback_with_source(self) -> Option<(T, Source)> {
+        self.and_then_async_with_source(|data| core::future::ready(Some(data)))
+            .await
+    }
+}
+
+/// A pinned future which keeps its output once it completes.
+enum MaybeDone<'a, F: Future> {
+    Future(Pin<&'a mut F>),
+    Done(F::Output),
+    Gone,
+}
+
+impl<F: Future> MaybeDone<'_, F> {
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Self::Future(future) = self {
+            let output = ready!(future.as_mut().poll(cx));
+            *self = Self::Done(output);
+        }
+        Poll::Ready(())
+    }
+
+    fn take(&mut self) -> Option<F::Output> {
+        match core::mem::replace(self, Self::Gone) {
+            Self::Done(output) => Some(output),
+            other => {
+                *self = other;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use std::{
+        cell::Cell,
+        sync::Arc,
+        task::{Wake, Waker},
+        thread::Thread,
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// A minimal executor which parks the thread until woken.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    /// Returns `value` after being polled `pending + 1` times, and counts the polls.
+    async fn delayed<T>(polls: &Cell<usize>, pending: usize, value: Option<T>) -> Option<T> {
+        poll_fn(|cx| {
+            polls.set(polls.get() + 1);
+            if polls.get() > pending {
+                Poll::Ready(())
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await;
+        value
+    }
+
+    #[test]
+    fn lazy_base() {
+        let data_polls = Cell::new(0);
+        let base_polls = Cell::new(0);
+        let fallback = AsyncFallback::new(
+            delayed(&data_polls, 2, Some("abc")),
+            delayed(&base_polls, 0, Some("123")),
+        );
+        assert_eq!(
+            block_on(fallback.fallback_with_source()),
+            Some(("abc", Source::Data))
+        );
+        assert_eq!(data_polls.get(), 3);
+        assert_eq!(base_polls.get(), 0);
+
+        let fallback = AsyncFallback::new(
+            delayed(&data_polls, 0, Some("abc")),
+            delayed(&base_polls, 0, Some("123")),
+        );
+        let num = block_on(fallback.and_then_async(|s| async move { s.parse::<i32>().ok() }));
+        assert_eq!(num, Some(123));
+        assert_eq!(base_polls.get(), 1);
+    }
+
+    #[test]
+    fn race() {
+        let data_polls = Cell::new(0);
+        let base_polls = Cell::new(0);
+        let fallback = AsyncFallback::new(
+            delayed(&data_polls, 2, Some(1)),
+            delayed(&base_polls, 0, Some(2)),
+        );
+        assert_eq!(block_on(fallback.race().fallback()), Some(1));
+        assert_eq!(base_polls.get(), 1);
+
+        let data_polls = Cell::new(0);
+        let base_polls = Cell::new(0);
+        let fallback = AsyncFallback::new(
+            delayed(&data_polls, 0, None),
+            delayed(&base_polls, 3, Some(2)),
+        );
+        assert_eq!(
+            block_on(fallback.race().fallback_with_source()),
+            Some((2, Source::Base))
+        );
+        assert_eq!(base_polls.get(), 4);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{Fallback, Merge};
+use alloc::{vec, vec::Vec};
+
+/// Stores any number of ordered [`Option`] layers, and provides functionality to fallback.
+///
+/// The first layer has the highest priority, and the last one is the final fallback.
+/// ```
+/// # use fallback::FallbackChain;
+/// let chain = FallbackChain::new(vec![None, Some("hello"), Some("123")]);
+/// let num = chain.and_then(|s| s.parse::<i32>().ok());
+/// assert_eq!(num, Some(123));
+/// ```
+///
+/// With the `serde` feature, it is (de)serialized as a sequence of layers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(::serde::Serialize, ::serde::Deserialize),
+    serde(transparent)
+)]
+pub struct FallbackChain<T> {
+    layers: Vec<Option<T>>,
+}
+
+impl<T> FallbackChain<T> {
+    /// Creates a new [`FallbackChain`], ordered from the highest priority to the lowest.
+    pub const fn new(layers: Vec<Option<T>>) -> Self {
+        Self { layers }
+    }
+
+    /// Returns the number of layers, including the [`None`] ones.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns `true` if there is no layer.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns `false` if all layers are [`None`].
+    pub fn is_some(&self) -> bool {
+        self.layers.iter().any(Option::is_some)
+    }
+
+    /// Converts from `&FallbackChain<T>` to `FallbackChain<&T>`.
+    pub fn as_ref(&self) -> FallbackChain<&T> {
+        FallbackChain::new(self.layers.iter().map(Option::as_ref).collect())
+    }
+
+    /// Fallbacks the data or part of data.
+    pub fn and_then<V>(self, mut f: impl FnMut(T) -> Option<V>) -> Option<V> {
+        self.layers
+            .into_iter()
+            .find_map(|data| data.and_then(&mut f))
+    }
+
+    /// Fallbacks the total data.
+    pub fn fallback(self) -> Option<T> {
+        self.layers.into_iter().flatten().next()
+    }
+
+    /// Returns the index of the layer which [`FallbackChain::fallback`] chooses.
+    pub fn layer(&self) -> Option<usize> {
+        self.layers.iter().position(Option::is_some)
+    }
+
+    /// Fallbacks the data or part of data, and reports the index of the layer it comes from.
+    pub fn and_then_with_layer<V>(self, mut f: impl FnMut(T) -> Option<V>) -> Option<(V, usize)> {
+        self.layers
+            .into_iter()
+            .enumerate()
+            .find_map(|(i, data)| data.and_then(&mut f).map(|v| (v, i)))
+    }
+
+    /// Fallbacks the total data, and reports the index of the layer it comes from.
+    pub fn fallback_with_layer(self) -> Option<(T, usize)> {
+        self.and_then_with_layer(Some)
+    }
+
+    /// Maps to a new [`FallbackChain`].
+    pub fn map<V>(self, mut f: impl FnMut(T) -> V) -> FallbackChain<V> {
+        FallbackChain::new(
+            self.layers
+                .into_iter()
+                .map(|data| data.map(&mut f))
+                .collect(),
+        )
+    }
+
+    /// Exacts the layers.
+    pub fn into_layers(self) -> Vec<Option<T>> {
+        self.layers
+    }
+
+    /// Merges the layers by `f`, from the last one to the first one,
+    /// where `f` takes the higher-priority value first.
+    pub fn merge_with(self, mut f: impl FnMut(T, T) -> T) -> Option<T> {
+        self.layers
+            .into_iter()
+            .rev()
+            .fold(None, |base, data| match (data, base) {
+                (Some(data), Some(base)) => Some(f(data, base)),
+                (data, base) => data.or(base),
+            })
+    }
+}
+
+impl<T: Merge> FallbackChain<T> {
+    /// Merges the layers, from the last one to the first one.
+    /// ```
+    /// # use fallback::FallbackChain;
+    /// let chain = FallbackChain::new(vec![Some(vec![3]), None, Some(vec![1, 2])]);
+    /// assert_eq!(chain.merge(), Some(vec![1, 2, 3]));
+    /// ```
+    pub fn merge(self) -> Option<T> {
+        self.merge_with(Merge::merge)
+    }
+}
+
+impl<T> FallbackChain<Option<T>> {
+    /// Converts from `FallbackChain<Option<T>>` to `FallbackChain<T>`.
+    pub fn flatten(self) -> FallbackChain<T> {
+        FallbackChain::new(self.layers.into_iter().map(Option::flatten).collect())
+    }
+}
+
+impl<T> FallbackChain<T>
+where
+    for<'a> &'a T: IntoIterator,
+    for<'a> <&'a T as IntoIterator>::IntoIter: ExactSizeIterator,
+{
+    /// Treats the empty container as [`None`] and fallbacks.
+    pub fn and_any(self) -> Option<T> {
+        self.and_then(|s| {
+            if s.into_iter().len() == 0 {
+                None
+            } else {
+                Some(s)
+            }
+        })
+    }
+}
+
+impl<T: AsRef<str>> FallbackChain<T> {
+    /// Treats the empty string as [`None`] and fallbacks.
+    pub fn and_any_str(self) -> Option<T> {
+        self.and_then(|s| if s.as_ref().is_empty() { None } else { Some(s) })
+    }
+}
+
+impl<T> Default for FallbackChain<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T> From<FallbackChain<T>> for Option<T> {
+    fn from(f: FallbackChain<T>) -> Self {
+        f.fallback()
+    }
+}
+
+impl<T> From<Fallback<T>> for FallbackChain<T> {
+    fn from(f: Fallback<T>) -> Self {
+        let (data, base_data) = f.unzip();
+        Self::new(vec![data, base_data])
+    }
+}
+
+impl<T> FromIterator<Option<T>> for FallbackChain<T> {
+    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/// An iterator zipping the layers by position,
+/// created by [`FallbackChain::into_iter`] and [`FallbackChain::positional_overlay`].
+pub struct FallbackChainIter<A> {
+    layers: Vec<Option<A>>,
+}
+
+impl<A: Iterator> Iterator for FallbackChainIter<A> {
+    type Item = FallbackChain<A::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let layers = self
+            .layers
+            .iter_mut()
+            .map(|data| data.as_mut().and_then(|data| data.next()))
+            .collect::<Vec<_>>();
+        if layers.iter().any(Option::is_some) {
+            Some(FallbackChain::new(layers))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: IntoIterator> IntoIterator for FallbackChain<T> {
+    type Item = FallbackChain<T::Item>;
+
+    type IntoIter = FallbackChainIter<core::iter::Fuse<T::IntoIter>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        FallbackChainIter {
+            layers: self
+                .layers
+                .into_iter()
+                .map(|data| data.map(|data| data.into_iter().fuse()))
+                .collect(),
+        }
+    }
+}
+
+/// This trait helps to create a new fallback chain type.
+///
+/// It is the [`FallbackChain`] counterpart of [`FallbackSpec`](crate::FallbackSpec),
+/// and is implemented by `#[derive(FallbackSpec)]` as well.
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// struct Foo {
+///     data1: i32,
+///     data2: String,
+/// }
+///
+/// let chain = FallbackChain::new(vec![
+///     None,
+///     Some(Foo { data1: 1, data2: String::new() }),
+///     Some(Foo { data1: 2, data2: "Hello".to_string() }),
+/// ]);
+/// let chain = chain.spec();
+/// assert_eq!(chain.data1.fallback(), Some(1));
+/// assert_eq!(chain.data2.and_any_str(), Some("Hello".to_string()));
+/// ```
+pub trait FallbackChainSpec: Sized {
+    /// The specialized fallback chain type.
+    type ChainSpecType: From<FallbackChain<Self>>;
+}
+
+impl<T: FallbackChainSpec> FallbackChain<T> {
+    /// Get the specialized fallback chain object.
+    pub fn spec(self) -> T::ChainSpecType {
+        T::ChainSpecType::from(self)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn some() {
+        assert!(!FallbackChain::<()>::new(vec![None, None, None]).is_some());
+        assert!(!FallbackChain::<()>::new(vec![]).is_some());
+    }
+
+    #[test]
+    fn option() {
+        let f = FallbackChain::new(vec![None, None, Some(100), Some(200)]);
+        assert_eq!(Option::from(f), Some(100));
+    }
+
+    #[test]
+    fn layer() {
+        let f = FallbackChain::new(vec![None, Some("abc"), Some("123")]);
+        assert_eq!(f.layer(), Some(1));
+        assert_eq!(
+            f.and_then_with_layer(|s| s.parse::<i32>().ok()),
+            Some((123, 2))
+        );
+    }
+
+    #[test]
+    fn from_fallback() {
+        let f = FallbackChain::from(Fallback::new(None, Some(100)));
+        assert_eq!(f.into_layers(), [None, Some(100)]);
+    }
+
+    #[test]
+    fn empty() {
+        let f = FallbackChain::new(vec![Some(vec![]), None, Some(vec![1, 1, 4, 5, 1, 4])]);
+        assert_eq!(f.and_any(), Some(vec![1, 1, 4, 5, 1, 4]));
+
+        let f = FallbackChain::new(vec![
+            Some(String::new()),
+            Some(String::new()),
+            Some("Hello world!".to_string()),
+        ]);
+        assert_eq!(f.and_any_str(), Some("Hello world!".to_string()));
+    }
+
+    #[test]
+    fn merge() {
+        let f = FallbackChain::new(vec![
+            Some(std::collections::BTreeSet::from([1])),
+            None,
+            Some(std::collections::BTreeSet::from([1, 2])),
+            Some(std::collections::BTreeSet::from([3])),
+        ]);
+        assert_eq!(
+            f.merge().unwrap().into_iter().collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(FallbackChain::<String>::new(vec![None]).merge(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let f = FallbackChain::new(vec![
+            Some(vec![3]),
+            Some(vec![3, 2, 1]),
+            Some(vec![1, 1, 4, 5, 1, 4]),
+        ]);
+        assert_eq!(
+            f.into_iter()
+                .map(|data| data.fallback().unwrap())
+                .collect::<Vec<_>>(),
+            [3, 2, 1, 5, 1, 4]
+        );
+    }
+}
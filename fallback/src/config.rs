@@ -0,0 +1,493 @@
+//! A layered configuration loader built on [`FallbackPartial`].
+//!
+//! Every source is deserialized into the partial type of the configuration,
+//! the partial layers are stacked, and the result is resolved into the full type,
+//! together with the source which supplies each field.
+//! ```
+//! # use fallback::{config::{Config, Format}, FallbackSpec};
+//! #[derive(FallbackSpec)]
+//! #[fallback(partial, serde)]
+//! struct Settings {
+//!     name: String,
+//!     port: u16,
+//! }
+//!
+//! let loaded = Config::new()
+//!     .str(Format::Json, r#"{"port": 8080}"#)
+//!     .defaults(Settings { name: "server".to_string(), port: 80 })
+//!     .load()
+//!     .unwrap();
+//! assert_eq!(loaded.value.name, "server");
+//! assert_eq!(loaded.value.port, 8080);
+//! assert_eq!(loaded.source_of("port"), Some("json string"));
+//! assert_eq!(loaded.source_of("name"), Some("defaults"));
+//! ```
+
+use crate::{FallbackPartial, MissingFields, Partial};
+use serde::{
+    de::{
+        value::MapDeserializer, Deserialize, DeserializeOwned, Deserializer, IntoDeserializer,
+        Unexpected, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+use serde_json::{Map, Value};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// The format of a configuration document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON.
+    Json,
+    /// TOML, with the `toml` feature.
+    #[cfg(feature = "toml")]
+    Toml,
+    /// YAML, with the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl Format {
+    /// Guesses the format from the extension of a file.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            #[cfg(feature = "toml")]
+            "toml" => Some(Self::Toml),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(self, s: &str) -> Result<Value, String> {
+        match self {
+            Self::Json => serde_json::from_str(s).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::from_str(s).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => serde_yaml::from_str(s).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json => f.write_str("json"),
+            #[cfg(feature = "toml")]
+            Self::Toml => f.write_str("toml"),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => f.write_str("yaml"),
+        }
+    }
+}
+
+enum Source<T> {
+    File { path: PathBuf, optional: bool },
+    Str { format: Format, content: String },
+    Env { prefix: String },
+    Map { map: Value },
+    Defaults { data: T },
+}
+
+impl<T> Display for Source<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File { path, .. } => write!(f, "file {}", path.display()),
+            Self::Str { format, .. } => write!(f, "{} string", format),
+            Self::Env { prefix } => write!(f, "env {}*", prefix),
+            Self::Map { .. } => f.write_str("map"),
+            Self::Defaults { .. } => f.write_str("defaults"),
+        }
+    }
+}
+
+/// The error when a configuration can't be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A file can't be read.
+    Io(PathBuf, std::io::Error),
+    /// The format of a file can't be guessed from its extension.
+    UnknownFormat(PathBuf),
+    /// A source can't be parsed or deserialized.
+    Parse {
+        /// The description of the source.
+        source: String,
+        /// The error message.
+        message: String,
+    },
+    /// Some fields are missing in every source.
+    Missing(MissingFields),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            Self::UnknownFormat(path) => write!(f, "unknown format of {}", path.display()),
+            Self::Parse { source, message } => write!(f, "cannot parse {}: {}", source, message),
+            Self::Missing(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(_, e) => Some(e),
+            Self::Missing(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<MissingFields> for ConfigError {
+    fn from(e: MissingFields) -> Self {
+        Self::Missing(e)
+    }
+}
+
+/// The loaded configuration.
+#[derive(Debug)]
+pub struct Loaded<T> {
+    /// The resolved value.
+    pub value: T,
+    /// The descriptions of the sources, in the order they are added.
+    pub sources: Vec<String>,
+    /// The field names, with the nested ones joined by `.`,
+    /// and the index of the source which supplies each of them.
+    pub provenance: Vec<(String, usize)>,
+}
+
+impl<T> Loaded<T> {
+    /// Returns the description of the source which supplies a field.
+    pub fn source_of(&self, field: &str) -> Option<&str> {
+        self.provenance
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, i)| self.sources[*i].as_str())
+    }
+}
+
+/// A builder of layered sources, ordered from the highest priority to the lowest.
+///
+/// The configuration type should derive [`FallbackSpec`](crate::FallbackSpec)
+/// with `#[fallback(partial, serde)]`.
+pub struct Config<T> {
+    sources: Vec<Source<T>>,
+}
+
+impl<T> Default for Config<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Config<T> {
+    /// Creates a [`Config`] without any source.
+    pub fn new() -> Self {
+        Self { sources: vec![] }
+    }
+
+    /// Adds a file, and guesses the format from the extension.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File {
+            path: path.into(),
+            optional: false,
+        });
+        self
+    }
+
+    /// Adds a file which is skipped if it doesn't exist.
+    pub fn optional_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File {
+            path: path.into(),
+            optional: true,
+        });
+        self
+    }
+
+    /// Adds a document in memory.
+    pub fn str(mut self, format: Format, content: impl Into<String>) -> Self {
+        self.sources.push(Source::Str {
+            format,
+            content: content.into(),
+        });
+        self
+    }
+
+    /// Adds the environment variables starting with `prefix`.
+    ///
+    /// The rest of the name is lowercased, and nested fields are separated by `__`,
+    /// e.g. `APP_INNER__DATA1` for `inner.data1` with prefix `APP_`.
+    /// A value is kept as a string, and parsed when the field is a number or a boolean,
+    /// or parsed as JSON when the field is a sequence, a map or a struct.
+    pub fn env(mut self, prefix: impl Into<String>) -> Self {
+        self.sources.push(Source::Env {
+            prefix: prefix.into(),
+        });
+        self
+    }
+
+    /// Adds a map in memory, where nested fields are separated by `.` in the keys.
+    pub fn map<K: AsRef<str>, V: Into<Value>>(
+        mut self,
+        map: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let mut value = Value::Object(Map::new());
+        for (key, v) in map {
+            insert_path(&mut value, key.as_ref().split('.'), v.into());
+        }
+        self.sources.push(Source::Map { map: value });
+        self
+    }
+
+    /// Adds the full defaults.
+    pub fn defaults(mut self, data: T) -> Self {
+        self.sources.push(Source::Defaults { data });
+        self
+    }
+}
+
+impl<T: FallbackPartial> Config<T>
+where
+    T::Partial: DeserializeOwned,
+{
+    /// Loads all sources, and resolves them into the full type.
+    pub fn load(self) -> Result<Loaded<T>, ConfigError> {
+        let mut sources = vec![];
+        let mut layers = vec![];
+        for source in self.sources {
+            sources.push(source.to_string());
+            if let Some(layer) = source.load()? {
+                layers.push((sources.len() - 1, layer));
+            }
+        }
+        let mut provenance: Vec<(String, usize)> = vec![];
+        for (i, layer) in &layers {
+            for name in layer.present_fields() {
+                if provenance.iter().all(|(n, _)| *n != name) {
+                    provenance.push((name, *i));
+                }
+            }
+            // The `base_only` fields are taken from the last layer holding them.
+            for name in layer.present_base_fields() {
+                match provenance.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, source)) => *source = *i,
+                    None => provenance.push((name, *i)),
+                }
+            }
+        }
+        let value =
+            T::Partial::from_layers(layers.into_iter().map(|(_, layer)| layer)).resolve(None)?;
+        Ok(Loaded {
+            value,
+            sources,
+            provenance,
+        })
+    }
+}
+
+impl<T: FallbackPartial> Source<T>
+where
+    T::Partial: DeserializeOwned,
+{
+    fn load(self) -> Result<Option<T::Partial>, ConfigError> {
+        let description = self.to_string();
+        let parse_error = |message| ConfigError::Parse {
+            source: description.clone(),
+            message,
+        };
+        let value = match &self {
+            Self::File { path, optional } => {
+                let format = Format::from_path(path)
+                    .ok_or_else(|| ConfigError::UnknownFormat(path.clone()))?;
+                let content = match std::fs::read_to_string(path) {
+                    Ok(content) => content,
+                    Err(e) if *optional && e.kind() == std::io::ErrorKind::NotFound => {
+                        return Ok(None)
+                    }
+                    Err(e) => return Err(ConfigError::Io(path.clone(), e)),
+                };
+                format.parse(&content)
+            }
+            Self::Str { format, content } => format.parse(content),
+            Self::Env { prefix } => {
+                let mut value = Value::Object(Map::new());
+                let vars = std::env::vars_os()
+                    .filter_map(|(key, v)| Some((key.into_string().ok()?, v.into_string().ok()?)));
+                for (key, v) in vars {
+                    if let Some(key) = key.strip_prefix(prefix.as_str()) {
+                        let key = key.to_lowercase();
+                        insert_path(&mut value, key.split("__"), Value::String(v));
+                    }
+                }
+                Ok(value)
+            }
+            Self::Map { map } => Ok(map.clone()),
+            Self::Defaults { .. } => Ok(Value::Null),
+        };
+        let value = value.map_err(parse_error)?;
+        let partial = match self {
+            Self::Defaults { data } => return Ok(Some(T::Partial::from(data))),
+            Self::Env { .. } => T::Partial::deserialize(EnvValue(value)),
+            _ => serde_json::from_value(value),
+        };
+        partial.map(Some).map_err(|e| parse_error(e.to_string()))
+    }
+}
+
+/// A value of environment variables, where the strings are parsed
+/// into the numbers and booleans which the target type asks for,
+/// and parsed as JSON when it asks for a sequence, a map or a struct.
+struct EnvValue(Value);
+
+impl EnvValue {
+    fn parse_json(self) -> Result<Self, serde_json::Error> {
+        match self.0 {
+            Value::String(s) => serde_json::from_str(&s).map(Self),
+            value => Ok(Self(value)),
+        }
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.0 {
+                    Value::String(s) => match s.parse() {
+                        Ok(v) => visitor.$visit(v),
+                        Err(_) => Err(serde::de::Error::invalid_value(Unexpected::Str(&s), &visitor)),
+                    },
+                    value => value.$method(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for EnvValue {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Object(map) => visitor.visit_map(MapDeserializer::new(
+                map.into_iter().map(|(key, v)| (key, EnvValue(v))),
+            )),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.parse_json()?.deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.parse_json()?.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            // A unit variant is a plain string, and the others are JSON maps.
+            Value::String(s) if s.starts_with('{') => {
+                serde_json::from_str::<Value>(&s)?.deserialize_enum(name, variants, visitor)
+            }
+            value => value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, serde_json::Error> for EnvValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn insert_path<'a>(value: &mut Value, mut path: impl Iterator<Item = &'a str>, v: Value) {
+    match path.next() {
+        Some(key) => {
+            if !value.is_object() {
+                *value = Value::Object(Map::new());
+            }
+            let entry = value
+                .as_object_mut()
+                .unwrap()
+                .entry(key)
+                .or_insert(Value::Null);
+            insert_path(entry, path, v);
+        }
+        None => *value = v,
+    }
+}
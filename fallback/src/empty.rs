@@ -0,0 +1,154 @@
+#[cfg(feature = "alloc")]
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    string::String,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+/// A value which may be empty, and is treated as [`None`]
+/// by a field marked with `#[fallback(empty_is_none)]`.
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// struct Foo {
+///     #[fallback(empty_is_none)]
+///     name: &'static str,
+///     #[fallback(when = "is_positive")]
+///     port: i32,
+/// }
+///
+/// fn is_positive(port: &i32) -> bool {
+///     *port > 0
+/// }
+///
+/// let data = Foo { name: "", port: -1 };
+/// let base_data = Foo { name: "base", port: 80 };
+/// let data = Fallback::new(Some(data), Some(base_data)).spec().resolve().unwrap();
+/// assert_eq!(data.name, "base");
+/// assert_eq!(data.port, 80);
+/// ```
+pub trait IsEmpty {
+    /// Returns `true` if the value is empty.
+    fn is_empty(&self) -> bool;
+}
+
+impl<T: IsEmpty + ?Sized> IsEmpty for &T {
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: IsEmpty + ?Sized> IsEmpty for Box<T> {
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+}
+
+impl<T> IsEmpty for Option<T> {
+    fn is_empty(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl IsEmpty for str {
+    fn is_empty(&self) -> bool {
+        str::is_empty(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl IsEmpty for String {
+    fn is_empty(&self) -> bool {
+        self.as_str().is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl IsEmpty for OsStr {
+    fn is_empty(&self) -> bool {
+        OsStr::is_empty(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl IsEmpty for OsString {
+    fn is_empty(&self) -> bool {
+        self.as_os_str().is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl IsEmpty for Path {
+    fn is_empty(&self) -> bool {
+        self.as_os_str().is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl IsEmpty for PathBuf {
+    fn is_empty(&self) -> bool {
+        self.as_os_str().is_empty()
+    }
+}
+
+impl<T> IsEmpty for [T] {
+    fn is_empty(&self) -> bool {
+        <[T]>::is_empty(self)
+    }
+}
+
+impl<T, const N: usize> IsEmpty for [T; N] {
+    fn is_empty(&self) -> bool {
+        N == 0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> IsEmpty for Vec<T> {
+    fn is_empty(&self) -> bool {
+        Vec::is_empty(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> IsEmpty for VecDeque<T> {
+    fn is_empty(&self) -> bool {
+        VecDeque::is_empty(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> IsEmpty for HashMap<K, V, S> {
+    fn is_empty(&self) -> bool {
+        HashMap::is_empty(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> IsEmpty for BTreeMap<K, V> {
+    fn is_empty(&self) -> bool {
+        BTreeMap::is_empty(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> IsEmpty for HashSet<T, S> {
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> IsEmpty for BTreeSet<T> {
+    fn is_empty(&self) -> bool {
+        BTreeSet::is_empty(self)
+    }
+}
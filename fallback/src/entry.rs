@@ -0,0 +1,111 @@
+use crate::{Fallback, Source};
+
+/// A view into a [`Fallback`] to edit its `data` in place,
+/// created by [`Fallback::entry`],
+/// or by the `entry_{field}` methods of a spec struct generated by `#[derive(FallbackSpec)]`.
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// struct Settings {
+///     theme: String,
+///     font_size: u32,
+/// }
+///
+/// let defaults = Settings { theme: "light".to_string(), font_size: 14 };
+/// let mut settings = Fallback::new(None, Some(defaults)).spec();
+/// settings.entry_theme().set("dark".to_string());
+/// settings.entry_font_size().and_modify(|size| *size += 2);
+/// assert_eq!(settings.entry_font_size().source(), Some(Source::Data));
+///
+/// let settings = settings.resolve().unwrap();
+/// assert_eq!(settings.theme, "dark");
+/// assert_eq!(settings.font_size, 16);
+/// ```
+#[derive(Debug)]
+pub struct Entry<'a, T> {
+    fallback: &'a mut Fallback<T>,
+}
+
+impl<T> Fallback<T> {
+    /// Gets the [`Entry`] to edit `data` in place.
+    pub fn entry(&mut self) -> Entry<'_, T> {
+        Entry::new(self)
+    }
+}
+
+impl<'a, T> Entry<'a, T> {
+    /// Creates a new [`Entry`].
+    pub fn new(fallback: &'a mut Fallback<T>) -> Self {
+        Self { fallback }
+    }
+
+    /// Returns a reference to the value which [`Fallback::fallback`] chooses.
+    pub fn get(&self) -> Option<&T> {
+        self.fallback.as_ref().fallback()
+    }
+
+    /// Returns the layer which supplies the value.
+    pub fn source(&self) -> Option<Source> {
+        self.fallback.source()
+    }
+
+    /// Sets `data` to `value`, and returns a mutable reference to it.
+    pub fn set(self, value: T) -> &'a mut T {
+        self.fallback.set_data(value)
+    }
+
+    /// Clears `data`, so that the value falls back to `base_data`, and returns the old `data`.
+    pub fn clear(self) -> Option<T> {
+        self.fallback.clear_data()
+    }
+
+    /// Copies the value of `base_data` into `data` if `data` is [`None`],
+    /// and returns a mutable reference to `data`.
+    pub fn promote(self) -> Option<&'a mut T>
+    where
+        T: Clone,
+    {
+        self.fallback.promote()
+    }
+
+    /// Promotes the value into `data` and modifies it with `f`,
+    /// or does nothing if there is no value.
+    pub fn and_modify(self, f: impl FnOnce(&mut T)) -> Self
+    where
+        T: Clone,
+    {
+        if let Some(data) = self.fallback.promote() {
+            f(data);
+        }
+        self
+    }
+
+    /// Converts into a mutable reference to the [`Fallback`].
+    pub fn into_mut(self) -> &'a mut Fallback<T> {
+        self.fallback
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn entry() {
+        let mut f = Fallback::new(None, Some(1));
+        assert_eq!(f.entry().get(), Some(&1));
+        assert_eq!(f.entry().source(), Some(Source::Base));
+        f.entry().and_modify(|n| *n += 1);
+        assert_eq!(f.unzip(), (Some(2), Some(1)));
+
+        let mut f = Fallback::new(Some(2), Some(1));
+        assert_eq!(f.entry().clear(), Some(2));
+        *f.entry().set(3) *= 2;
+        assert_eq!(f.entry().into_mut().clear_data(), Some(6));
+        assert_eq!(*f.entry().promote().unwrap(), 1);
+
+        let mut f = Fallback::<i32>::new(None, None);
+        f.entry().and_modify(|_| unreachable!());
+        assert!(f.is_none());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{Fallback, FallbackChain, Source};
+use alloc::{format, vec, vec::Vec};
+
+/// The errors of every layer, returned by [`Fallback::try_and_then`],
+/// [`LazyFallback::try_and_then`](crate::LazyFallback::try_and_then)
+/// and [`FallbackChain::try_and_then`] when no layer succeeds.
+///
+/// Each error is tagged with the layer it comes from,
+/// i.e., a [`Source`] for [`Fallback`], or an index for [`FallbackChain`].
+/// The [`None`] layers are skipped.
+/// ```
+/// # use fallback::*;
+/// let fallback = Fallback::new(Some("abc"), Some("1.5"));
+/// let err = fallback.try_and_then(|s| s.parse::<i32>()).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "data: invalid digit found in string; base: invalid digit found in string"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerErrors<E, L = Source> {
+    errors: Vec<(E, L)>,
+}
+
+impl<E, L> LayerErrors<E, L> {
+    /// Creates a new [`LayerErrors`] with the errors and their layers.
+    pub fn new(errors: Vec<(E, L)>) -> Self {
+        Self { errors }
+    }
+
+    /// Returns `true` if every layer is [`None`], so that there is no error.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The errors with their layers, ordered from the highest priority to the lowest.
+    pub fn errors(&self) -> &[(E, L)] {
+        &self.errors
+    }
+
+    /// Exacts the errors with their layers.
+    pub fn into_errors(self) -> Vec<(E, L)> {
+        self.errors
+    }
+}
+
+impl<E: core::fmt::Display, L: core::fmt::Display> core::fmt::Display for LayerErrors<E, L> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.errors.is_empty() {
+            return f.write_str("no layer has a value");
+        }
+        let errors = self
+            .errors
+            .iter()
+            .map(|(error, layer)| format!("{}: {}", layer, error))
+            .collect::<Vec<_>>();
+        f.write_str(&errors.join("; "))
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display, L: core::fmt::Debug + core::fmt::Display>
+    core::error::Error for LayerErrors<E, L>
+{
+}
+
+impl<T> Fallback<T> {
+    /// Fallbacks the data or part of data, where `f` may fail.
+    ///
+    /// Returns the first success, or the errors of all layers.
+    pub fn try_and_then<V, E>(
+        self,
+        mut f: impl FnMut(T) -> Result<V, E>,
+    ) -> Result<V, LayerErrors<E>> {
+        let (data, base_data) = self.unzip();
+        let mut errors = vec![];
+        for (layer, source) in [(data, Source::Data), (base_data, Source::Base)] {
+            if let Some(layer) = layer {
+                match f(layer) {
+                    Ok(v) => return Ok(v),
+                    Err(e) => errors.push((e, source)),
+                }
+            }
+        }
+        Err(LayerErrors::new(errors))
+    }
+}
+
+impl<T> FallbackChain<T> {
+    /// Fallbacks the data or part of data, where `f` may fail.
+    ///
+    /// Returns the first success, or the errors of all layers tagged with their indices.
+    pub fn try_and_then<V, E>(
+        self,
+        mut f: impl FnMut(T) -> Result<V, E>,
+    ) -> Result<V, LayerErrors<E, usize>> {
+        let mut errors = vec![];
+        for (i, layer) in self.into_layers().into_iter().enumerate() {
+            if let Some(layer) = layer {
+                match f(layer) {
+                    Ok(v) => return Ok(v),
+                    Err(e) => errors.push((e, i)),
+                }
+            }
+        }
+        Err(LayerErrors::new(errors))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn try_and_then() {
+        let f = Fallback::new(Some("abc"), Some("123"));
+        assert_eq!(f.try_and_then(|s| s.parse::<i32>()), Ok(123));
+
+        let f = Fallback::new(None, Some("abc"));
+        let err = f.try_and_then(|s| s.parse::<i32>()).unwrap_err();
+        assert_eq!(err.errors().len(), 1);
+        assert_eq!(err.errors()[0].1, Source::Base);
+
+        let f = Fallback::<&str>::new(None, None);
+        let err = f.try_and_then(|s| s.parse::<i32>()).unwrap_err();
+        assert!(err.is_empty());
+        assert_eq!(err.to_string(), "no layer has a value");
+
+        let f = FallbackChain::new(vec![Some("a"), None, Some("b")]);
+        let err = f
+            .try_and_then(|s| Err::<(), _>(format!("'{}' invalid", s)))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "0: 'a' invalid; 2: 'b' invalid");
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{Fallback, FallbackIter};
+#[cfg(feature = "alloc")]
+use crate::{FallbackChain, FallbackChainIter};
+#[cfg(feature = "alloc")]
+use alloc::vec::{self, Vec};
+#[cfg(feature = "std")]
+use core::hash::Hash;
+#[cfg(feature = "alloc")]
+use core::iter::Flatten;
+use core::iter::{Fuse, Peekable};
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+/// An iterator over the items of all layers, from the first layer to the last one,
+/// created by [`FallbackChain::chain`].
+#[cfg(feature = "alloc")]
+pub struct ChainIter<A> {
+    layers: Flatten<vec::IntoIter<Option<A>>>,
+    current: Option<A>,
+}
+
+#[cfg(feature = "alloc")]
+impl<A: Iterator> Iterator for ChainIter<A> {
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(item);
+            }
+            self.current = Some(self.layers.next()?);
+        }
+    }
+}
+
+/// An iterator over the items of `data`, and then the items of `base_data`,
+/// created by [`Fallback::chain`].
+pub struct PairChainIter<A> {
+    data: Option<A>,
+    base_data: Option<A>,
+}
+
+impl<A: Iterator> Iterator for PairChainIter<A> {
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.data.as_mut().and_then(Iterator::next) {
+            return Some(item);
+        }
+        self.data = None;
+        self.base_data.as_mut()?.next()
+    }
+}
+
+/// An iterator over the items of the first non-empty layer,
+/// created by [`Fallback::first_nonempty`].
+#[cfg_attr(
+    feature = "alloc",
+    doc = "It is also created by [`FallbackChain::first_nonempty`]."
+)]
+pub struct FirstNonempty<A: Iterator> {
+    iter: Option<Peekable<A>>,
+}
+
+impl<A: Iterator> Iterator for FirstNonempty<A> {
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.as_mut()?.next()
+    }
+}
+
+/// An iterator over the items of all layers, from the first layer to the last one,
+/// skipping the items whose key has been seen,
+/// created by [`Fallback::dedup_union_by_key`] and [`FallbackChain::dedup_union_by_key`].
+#[cfg(feature = "std")]
+pub struct DedupUnion<A, F, K> {
+    iter: ChainIter<A>,
+    key: F,
+    seen: HashSet<K>,
+}
+
+#[cfg(feature = "std")]
+impl<A: Iterator, F: FnMut(&A::Item) -> K, K: Eq + Hash> Iterator for DedupUnion<A, F, K> {
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = &mut self.key;
+        let seen = &mut self.seen;
+        self.iter.find(|item| seen.insert(key(item)))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: IntoIterator> FallbackChain<T> {
+    /// Iterates the items of all layers, from the first layer to the last one.
+    pub fn chain(self) -> ChainIter<T::IntoIter> {
+        ChainIter {
+            layers: self
+                .into_layers()
+                .into_iter()
+                .map(|data| data.map(IntoIterator::into_iter))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .flatten(),
+            current: None,
+        }
+    }
+
+    /// Iterates the items of the first layer which is neither [`None`] nor empty.
+    pub fn first_nonempty(self) -> FirstNonempty<T::IntoIter> {
+        let iter = self
+            .into_layers()
+            .into_iter()
+            .flatten()
+            .map(|data| data.into_iter().peekable())
+            .find_map(|mut iter| iter.peek().is_some().then_some(iter));
+        FirstNonempty { iter }
+    }
+
+    /// Zips the layers by position, as [`IntoIterator`] does.
+    pub fn positional_overlay(self) -> FallbackChainIter<Fuse<T::IntoIter>> {
+        self.into_iter()
+    }
+
+    /// Iterates the items of all layers, from the first layer to the last one,
+    /// and skips the items whose key returned by `key` has been seen.
+    #[cfg(feature = "std")]
+    pub fn dedup_union_by_key<K: Eq + Hash, F: FnMut(&T::Item) -> K>(
+        self,
+        key: F,
+    ) -> DedupUnion<T::IntoIter, F, K> {
+        DedupUnion {
+            iter: self.chain(),
+            key,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Iterates the items of all layers, from the first layer to the last one,
+    /// and skips the items equal to one that has been seen.
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    pub fn dedup_union(self) -> DedupUnion<T::IntoIter, fn(&T::Item) -> T::Item, T::Item>
+    where
+        T::Item: Eq + Hash + Clone,
+    {
+        self.dedup_union_by_key(Clone::clone as fn(&T::Item) -> T::Item)
+    }
+}
+
+impl<T: IntoIterator> Fallback<T> {
+    /// Iterates the items of `data`, and then the items of `base_data`.
+    /// ```
+    /// # use fallback::Fallback;
+    /// let fallback = Fallback::new(Some(vec![1, 2]), Some(vec![3, 2, 1]));
+    /// assert_eq!(fallback.chain().collect::<Vec<_>>(), [1, 2, 3, 2, 1]);
+    /// ```
+    pub fn chain(self) -> PairChainIter<T::IntoIter> {
+        let (data, base_data) = self.unzip();
+        PairChainIter {
+            data: data.map(IntoIterator::into_iter),
+            base_data: base_data.map(IntoIterator::into_iter),
+        }
+    }
+
+    /// Iterates the items of `data` if it is neither [`None`] nor empty,
+    /// or the items of `base_data` otherwise.
+    /// ```
+    /// # use fallback::Fallback;
+    /// let fallback = Fallback::new(Some(vec![]), Some(vec![3, 2, 1]));
+    /// assert_eq!(fallback.first_nonempty().collect::<Vec<_>>(), [3, 2, 1]);
+    /// ```
+    pub fn first_nonempty(self) -> FirstNonempty<T::IntoIter> {
+        let (data, base_data) = self.unzip();
+        let iter = data
+            .into_iter()
+            .chain(base_data)
+            .map(|data| data.into_iter().peekable())
+            .find_map(|mut iter| iter.peek().is_some().then_some(iter));
+        FirstNonempty { iter }
+    }
+
+    /// Zips `data` and `base_data` by position, as [`IntoIterator`] does.
+    /// ```
+    /// # use fallback::Fallback;
+    /// let fallback = Fallback::new(Some(vec![1, 2]), Some(vec![3, 2, 1]));
+    /// let items = fallback.positional_overlay().map(|data| data.fallback().unwrap());
+    /// assert_eq!(items.collect::<Vec<_>>(), [1, 2, 1]);
+    /// ```
+    pub fn positional_overlay(self) -> FallbackIter<Fuse<T::IntoIter>> {
+        self.into_iter()
+    }
+
+    /// Iterates the items of `data`, and then the items of `base_data`,
+    /// and skips the items whose key returned by `key` has been seen.
+    /// ```
+    /// # use fallback::Fallback;
+    /// let data = vec![("a", 1), ("b", 2)];
+    /// let base_data = vec![("b", 20), ("c", 30)];
+    /// let fallback = Fallback::new(Some(data), Some(base_data));
+    /// let items = fallback.dedup_union_by_key(|(key, _)| *key);
+    /// assert_eq!(items.collect::<Vec<_>>(), [("a", 1), ("b", 2), ("c", 30)]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn dedup_union_by_key<K: Eq + Hash, F: FnMut(&T::Item) -> K>(
+        self,
+        key: F,
+    ) -> DedupUnion<T::IntoIter, F, K> {
+        FallbackChain::from(self).dedup_union_by_key(key)
+    }
+
+    /// Iterates the items of `data`, and then the items of `base_data`,
+    /// and skips the items equal to one that has been seen.
+    /// ```
+    /// # use fallback::Fallback;
+    /// let fallback = Fallback::new(Some(vec![1, 2]), Some(vec![3, 2, 1]));
+    /// assert_eq!(fallback.dedup_union().collect::<Vec<_>>(), [1, 2, 3]);
+    /// ```
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    pub fn dedup_union(self) -> DedupUnion<T::IntoIter, fn(&T::Item) -> T::Item, T::Item>
+    where
+        T::Item: Eq + Hash + Clone,
+    {
+        FallbackChain::from(self).dedup_union()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn modes() {
+        let f = FallbackChain::new(vec![Some(vec![]), None, Some(vec![3, 1]), Some(vec![1, 2])]);
+        assert_eq!(f.clone().chain().collect::<Vec<_>>(), [3, 1, 1, 2]);
+        assert_eq!(f.clone().first_nonempty().collect::<Vec<_>>(), [3, 1]);
+        assert_eq!(f.clone().dedup_union().collect::<Vec<_>>(), [3, 1, 2]);
+        assert_eq!(
+            f.positional_overlay()
+                .map(|data| data.fallback().unwrap())
+                .collect::<Vec<_>>(),
+            [3, 1]
+        );
+
+        let f = Fallback::<Vec<i32>>::new(None, Some(vec![]));
+        assert_eq!(f.first_nonempty().next(), None);
+    }
+}
@@ -0,0 +1,230 @@
+#[cfg(feature = "alloc")]
+use crate::LayerErrors;
+use crate::{Fallback, Source};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Stores an [`Option`] and a function to compute the base data,
+/// which is called only when `data` doesn't supply a value.
+/// ```
+/// # use fallback::Fallback;
+/// let fallback = Fallback::lazy(Some("abc"), || Some("123"));
+/// let num = fallback.and_then(|s| s.parse::<i32>().ok());
+/// assert_eq!(num, Some(123));
+///
+/// let fallback = Fallback::lazy(Some(1), || -> Option<i32> { unreachable!() });
+/// assert_eq!(fallback.fallback(), Some(1));
+/// ```
+///
+/// The combinators which need both layers, like `unzip`, `merge` and the iteration modes,
+/// are left out on purpose, as they would always compute `base_data`.
+/// Call [`LazyFallback::force`] to use them on a [`Fallback`].
+pub struct LazyFallback<T, F> {
+    data: Option<T>,
+    base_data: F,
+}
+
+impl<T> Fallback<T> {
+    /// Creates a new [`LazyFallback`], where `base_data` is called only when needed.
+    pub const fn lazy<F: FnOnce() -> Option<T>>(
+        data: Option<T>,
+        base_data: F,
+    ) -> LazyFallback<T, F> {
+        LazyFallback::new(data, base_data)
+    }
+}
+
+impl<T, F: FnOnce() -> Option<T>> LazyFallback<T, F> {
+    /// Creates a new [`LazyFallback`].
+    pub const fn new(data: Option<T>, base_data: F) -> Self {
+        Self { data, base_data }
+    }
+
+    /// Converts from `&LazyFallback<T, F>` to `Option<&T>` of `data`,
+    /// without computing `base_data`.
+    pub const fn data(&self) -> Option<&T> {
+        self.data.as_ref()
+    }
+
+    /// Fallbacks the data or part of data.
+    pub fn and_then<V>(self, f: impl FnMut(T) -> Option<V>) -> Option<V> {
+        self.and_then_with_source(f).map(|(v, _)| v)
+    }
+
+    /// Fallbacks the total data.
+    pub fn fallback(self) -> Option<T> {
+        self.and_then(Some)
+    }
+
+    /// Fallbacks the total data, or returns `default`.
+    pub fn unwrap_or(self, default: T) -> T {
+        self.fallback().unwrap_or(default)
+    }
+
+    /// Fallbacks the total data, or computes it from `f`.
+    pub fn unwrap_or_else(self, f: impl FnOnce() -> T) -> T {
+        self.fallback().unwrap_or_else(f)
+    }
+
+    /// Fallbacks the total data, or returns the default value.
+    pub fn unwrap_or_default(self) -> T
+    where
+        T: Default,
+    {
+        self.fallback().unwrap_or_default()
+    }
+
+    /// Fallbacks the total data into [`Ok`], or returns [`Err`] with `err`.
+    pub fn ok_or<E>(self, err: E) -> Result<T, E> {
+        self.fallback().ok_or(err)
+    }
+
+    /// Fallbacks the total data into [`Ok`], or returns [`Err`] computed from `err`.
+    pub fn ok_or_else<E>(self, err: impl FnOnce() -> E) -> Result<T, E> {
+        self.fallback().ok_or_else(err)
+    }
+
+    /// Fallbacks the total data and maps it with `f`, or returns `default`.
+    pub fn map_or<V>(self, default: V, f: impl FnOnce(T) -> V) -> V {
+        self.fallback().map_or(default, f)
+    }
+
+    /// Fallbacks the data or part of data, where `f` may fail.
+    ///
+    /// Returns the first success, or the errors of all layers.
+    /// `base_data` is computed only when `data` doesn't succeed.
+    #[cfg(feature = "alloc")]
+    pub fn try_and_then<V, E>(
+        self,
+        mut f: impl FnMut(T) -> Result<V, E>,
+    ) -> Result<V, LayerErrors<E>> {
+        let mut errors = Vec::new();
+        if let Some(data) = self.data {
+            match f(data) {
+                Ok(v) => return Ok(v),
+                Err(e) => errors.push((e, Source::Data)),
+            }
+        }
+        if let Some(base_data) = (self.base_data)() {
+            match f(base_data) {
+                Ok(v) => return Ok(v),
+                Err(e) => errors.push((e, Source::Base)),
+            }
+        }
+        Err(LayerErrors::new(errors))
+    }
+
+    /// Fallbacks the data or part of data, and reports the layer it comes from.
+    pub fn and_then_with_source<V>(self, mut f: impl FnMut(T) -> Option<V>) -> Option<(V, Source)> {
+        self.data
+            .and_then(&mut f)
+            .map(|v| (v, Source::Data))
+            .or_else(|| {
+                (self.base_data)()
+                    .and_then(&mut f)
+                    .map(|v| (v, Source::Base))
+            })
+    }
+
+    /// Fallbacks the total data, and reports the layer it comes from.
+    pub fn fallback_with_source(self) -> Option<(T, Source)> {
+        self.and_then_with_source(Some)
+    }
+
+    /// Maps to a new [`LazyFallback`], where `f` is called on `base_data` only when it is computed.
+    pub fn map<V>(self, mut f: impl FnMut(T) -> V) -> LazyFallback<V, impl FnOnce() -> Option<V>> {
+        let data = self.data.map(&mut f);
+        let base_data = self.base_data;
+        LazyFallback::new(data, move || base_data().map(f))
+    }
+
+    /// Computes `base_data` and converts to a [`Fallback`].
+    pub fn force(self) -> Fallback<T> {
+        Fallback::new(self.data, (self.base_data)())
+    }
+}
+
+impl<T, F: FnOnce() -> Option<Option<T>>> LazyFallback<Option<T>, F> {
+    /// Converts from `LazyFallback<Option<T>, F>` to a [`LazyFallback`] of `T`.
+    pub fn flatten(self) -> LazyFallback<T, impl FnOnce() -> Option<T>> {
+        let base_data = self.base_data;
+        LazyFallback::new(self.data.flatten(), move || base_data().flatten())
+    }
+}
+
+impl<T, F: FnOnce() -> Option<T>> LazyFallback<T, F>
+where
+    for<'a> &'a T: IntoIterator,
+    for<'a> <&'a T as IntoIterator>::IntoIter: ExactSizeIterator,
+{
+    /// Treats the empty container as [`None`] and fallbacks.
+    pub fn and_any(self) -> Option<T> {
+        self.and_then(|s| {
+            if s.into_iter().len() == 0 {
+                None
+            } else {
+                Some(s)
+            }
+        })
+    }
+}
+
+impl<T: AsRef<str>, F: FnOnce() -> Option<T>> LazyFallback<T, F> {
+    /// Treats the empty string as [`None`] and fallbacks.
+    pub fn and_any_str(self) -> Option<T> {
+        self.and_then(|s| if s.as_ref().is_empty() { None } else { Some(s) })
+    }
+}
+
+impl<T, F: FnOnce() -> Option<T>> From<LazyFallback<T, F>> for Fallback<T> {
+    fn from(f: LazyFallback<T, F>) -> Self {
+        f.force()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn lazy() {
+        let calls = Cell::new(0);
+        let base = || {
+            calls.set(calls.get() + 1);
+            Some("123".to_string())
+        };
+
+        assert_eq!(
+            Fallback::lazy(Some("1".to_string()), base).fallback(),
+            Some("1".to_string())
+        );
+        assert_eq!(calls.get(), 0);
+
+        let f = Fallback::lazy(Some(String::new()), base);
+        assert_eq!(f.and_any_str(), Some("123".to_string()));
+        assert_eq!(calls.get(), 1);
+
+        let f = Fallback::lazy(Some("abc".to_string()), base).map(|s| s.len());
+        assert_eq!(calls.get(), 1);
+        assert_eq!(f.and_then_with_source(|n| (n > 3).then_some(n)), None);
+        assert_eq!(calls.get(), 2);
+
+        let f = Fallback::lazy(None, base).force();
+        assert_eq!(f.source(), Some(Source::Base));
+        assert_eq!(calls.get(), 3);
+
+        let f = Fallback::lazy(Some("1".to_string()), base);
+        assert_eq!(f.map_or(0, |s| s.len()), 1);
+        let f = Fallback::lazy(Some(None), || Some(base()));
+        assert_eq!(f.flatten().unwrap_or_default(), "123");
+        assert_eq!(calls.get(), 4);
+
+        let f = Fallback::lazy(Some("abc".to_string()), base);
+        assert_eq!(f.try_and_then(|s| s.parse::<i32>()), Ok(123));
+        assert_eq!(calls.get(), 5);
+        let f = Fallback::lazy(Some("1".to_string()), base);
+        assert_eq!(f.try_and_then(|s| s.parse::<i32>()), Ok(1));
+        assert_eq!(calls.get(), 5);
+    }
+}
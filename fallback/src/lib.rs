@@ -2,10 +2,153 @@
 //!
 //! [`Fallback`] type provides functionality to fallback
 //! if a value or a part of value doesn't exist.
+#![cfg_attr(
+    feature = "alloc",
+    doc = "[`FallbackChain`] type extends it to any number of layers."
+)]
+#![cfg_attr(
+    not(feature = "alloc"),
+    doc = "`FallbackChain` type extends it to any number of layers."
+)]
+//!
+//! The crate is `no_std` without the default `std` feature.
+//! The `alloc` feature enables the types which need allocation,
+//! including `FallbackChain` and the chain spec types of `#[derive(FallbackSpec)]`,
+//! and the `std` feature enables the helpers for `HashMap`, `HashSet` and paths.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![deny(unsafe_code)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec, vec::Vec};
+
+#[cfg(feature = "alloc")]
+mod chain;
+#[cfg(feature = "alloc")]
+pub use chain::*;
+
+mod partial;
+pub use partial::*;
+
+mod variant;
+pub use variant::*;
+
+mod empty;
+pub use empty::*;
+
+#[cfg(feature = "alloc")]
+mod map;
+#[cfg(feature = "alloc")]
+pub use map::*;
+
+mod iter;
+pub use iter::*;
+
+mod lazy;
+pub use lazy::*;
+
+mod entry;
+pub use entry::*;
+
+#[cfg(feature = "alloc")]
+mod error;
+#[cfg(feature = "alloc")]
+pub use error::*;
+
+pub mod merge;
+pub use merge::Merge;
+
+pub mod r#async;
+
+#[cfg(feature = "serde")]
+pub mod serde_impl;
+
+#[cfg(feature = "config")]
+pub mod config;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    use alloc::{format, string::ToString};
+
+    #[cfg(feature = "alloc")]
+    pub use alloc::{string::String, vec::Vec};
+
+    /// Joins the name of a field and the name of its nested field.
+    #[cfg(feature = "alloc")]
+    pub fn join_field(name: &str, field: &str) -> String {
+        if field.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", name, field)
+        }
+    }
+
+    /// The predicate of `#[fallback(empty_is_none)]`.
+    pub fn is_not_empty<T: crate::IsEmpty + ?Sized>(value: &T) -> bool {
+        !value.is_empty()
+    }
+
+    /// Filters every layer with `predicate`.
+    #[cfg(feature = "alloc")]
+    pub fn filter_layers<T>(
+        layers: Vec<Option<T>>,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Vec<Option<T>> {
+        layers
+            .into_iter()
+            .map(|layer| layer.filter(|value| predicate(value)))
+            .collect()
+    }
+
+    /// Keeps only the first layer, for `#[fallback(data_only)]`.
+    #[cfg(feature = "alloc")]
+    pub fn first_layer<T>(mut layers: Vec<Option<T>>) -> Vec<Option<T>> {
+        layers.iter_mut().skip(1).for_each(|layer| *layer = None);
+        layers
+    }
+
+    /// Keeps only the last layer, for `#[fallback(base_only)]`.
+    #[cfg(feature = "alloc")]
+    pub fn last_layer<T>(mut layers: Vec<Option<T>>) -> Vec<Option<T>> {
+        let len = layers.len();
+        layers
+            .iter_mut()
+            .take(len.saturating_sub(1))
+            .for_each(|layer| *layer = None);
+        layers
+    }
+
+    #[cfg(feature = "serde")]
+    pub use ::serde;
+
+    pub use crate::__alloc_only as alloc_only;
+}
+
+/// Keeps the items only with the `alloc` feature,
+/// so that the derive output follows the features of this crate.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __alloc_only {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+/// Keeps the items only with the `alloc` feature,
+/// so that the derive output follows the features of this crate.
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __alloc_only {
+    ($($item:tt)*) => {};
+}
+
 /// Stores two [`Option`], and provides functionality to fallback.
 ///
 /// Basically, you provides a function returns [`Option`],
@@ -28,8 +171,25 @@
 /// let s = fallback.and_then(|s| if s.len() > 3 { Some(s) } else { None });
 /// assert_eq!(s, Some("123456".to_string()));
 /// ```
+/// And you can tell which layer a value comes from:
+/// ```
+/// # use fallback::{Fallback, Source};
+/// let fallback = Fallback::new(Some("hello"), Some("123"));
+/// let num = fallback.and_then_with_source(|s| s.parse::<i32>().ok());
+/// assert_eq!(num, Some((123, Source::Base)));
+/// ```
+///
+/// With the `serde` feature, it is (de)serialized as a `{data, base}` pair,
+/// where a missing key is [`None`].
+#[cfg_attr(
+    feature = "serde",
+    doc = "See [`resolved`](crate::serde_impl::resolved) for the transparent form."
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct Fallback<T> {
     data: Option<T>,
+    #[cfg_attr(feature = "serde", serde(rename = "base"))]
     base_data: Option<T>,
 }
 
@@ -44,11 +204,124 @@ impl<T> Fallback<T> {
         self.data.is_some() || self.base_data.is_some()
     }
 
+    /// Returns `true` if both `data` and `base_data` are [`None`].
+    pub const fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// Returns `true` if `data` is not [`None`], i.e., the value comes from `data`.
+    pub const fn is_data(&self) -> bool {
+        self.data.is_some()
+    }
+
+    /// Returns `true` if only `base_data` is not [`None`], i.e., the value comes from `base_data`.
+    pub const fn is_base_only(&self) -> bool {
+        self.data.is_none() && self.base_data.is_some()
+    }
+
     /// Converts from `&Fallback<T>` to `Fallback<&T>`.
     pub const fn as_ref(&self) -> Fallback<&T> {
         Fallback::new(self.data.as_ref(), self.base_data.as_ref())
     }
 
+    /// Converts from `&mut Fallback<T>` to `Fallback<&mut T>`.
+    pub fn as_mut(&mut self) -> Fallback<&mut T> {
+        Fallback::new(self.data.as_mut(), self.base_data.as_mut())
+    }
+
+    /// Converts from `&Fallback<T>` to `Fallback<&T::Target>`.
+    pub fn as_deref(&self) -> Fallback<&T::Target>
+    where
+        T: core::ops::Deref,
+    {
+        Fallback::new(self.data.as_deref(), self.base_data.as_deref())
+    }
+
+    /// Converts from `&mut Fallback<T>` to `Fallback<&mut T::Target>`.
+    pub fn as_deref_mut(&mut self) -> Fallback<&mut T::Target>
+    where
+        T: core::ops::DerefMut,
+    {
+        Fallback::new(self.data.as_deref_mut(), self.base_data.as_deref_mut())
+    }
+
+    /// Filters each layer with `predicate`.
+    /// ```
+    /// # use fallback::Fallback;
+    /// let fallback = Fallback::new(Some(-1), Some(80));
+    /// assert_eq!(fallback.filter(|port| *port > 0).fallback(), Some(80));
+    /// ```
+    pub fn filter(self, mut predicate: impl FnMut(&T) -> bool) -> Self {
+        Fallback::new(
+            self.data.filter(&mut predicate),
+            self.base_data.filter(&mut predicate),
+        )
+    }
+
+    /// Zips with another [`Fallback`] layer by layer.
+    pub fn zip<U>(self, other: Fallback<U>) -> Fallback<(T, U)> {
+        Fallback::new(
+            self.data.zip(other.data),
+            self.base_data.zip(other.base_data),
+        )
+    }
+
+    /// Takes the layers out, leaving both [`None`].
+    pub fn take(&mut self) -> Self {
+        Fallback::new(self.data.take(), self.base_data.take())
+    }
+
+    /// Replaces `data` with `value`, and returns the old `data`.
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        self.data.replace(value)
+    }
+
+    /// Sets `data` to `value`, and returns a mutable reference to it.
+    pub fn set_data(&mut self, value: T) -> &mut T {
+        self.data.insert(value)
+    }
+
+    /// Clears `data`, so that the value falls back to `base_data`, and returns the old `data`.
+    pub fn clear_data(&mut self) -> Option<T> {
+        self.data.take()
+    }
+
+    /// Sets `base_data` to `value`, and returns a mutable reference to it.
+    pub fn set_base(&mut self, value: T) -> &mut T {
+        self.base_data.insert(value)
+    }
+
+    /// Returns a mutable reference to `data`.
+    pub fn data_mut(&mut self) -> &mut Option<T> {
+        &mut self.data
+    }
+
+    /// Returns a mutable reference to `base_data`.
+    pub fn base_mut(&mut self) -> &mut Option<T> {
+        &mut self.base_data
+    }
+
+    /// Copies the value of `base_data` into `data` if `data` is [`None`],
+    /// and returns a mutable reference to `data`.
+    ///
+    /// It is useful to edit the value, while keeping `base_data` intact.
+    /// ```
+    /// # use fallback::{Fallback, Source};
+    /// let mut fallback = Fallback::new(None, Some(vec![1, 2]));
+    /// fallback.promote().unwrap().push(3);
+    /// assert_eq!(fallback.source(), Some(Source::Data));
+    /// assert_eq!(fallback.unzip(), (Some(vec![1, 2, 3]), Some(vec![1, 2])));
+    /// ```
+    pub fn promote(&mut self) -> Option<&mut T>
+    where
+        T: Clone,
+    {
+        if self.data.is_none() {
+            self.data = self.base_data.clone();
+        }
+        self.data.as_mut()
+    }
+
     /// Fallbacks the data or part of data.
     pub fn and_then<V>(self, mut f: impl FnMut(T) -> Option<V>) -> Option<V> {
         self.data
@@ -61,6 +334,63 @@ impl<T> Fallback<T> {
         self.data.or(self.base_data)
     }
 
+    /// Fallbacks the total data, or returns `default`.
+    pub fn unwrap_or(self, default: T) -> T {
+        self.fallback().unwrap_or(default)
+    }
+
+    /// Fallbacks the total data, or computes it from `f`.
+    pub fn unwrap_or_else(self, f: impl FnOnce() -> T) -> T {
+        self.fallback().unwrap_or_else(f)
+    }
+
+    /// Fallbacks the total data, or returns the default value.
+    pub fn unwrap_or_default(self) -> T
+    where
+        T: Default,
+    {
+        self.fallback().unwrap_or_default()
+    }
+
+    /// Fallbacks the total data into [`Ok`], or returns [`Err`] with `err`.
+    pub fn ok_or<E>(self, err: E) -> Result<T, E> {
+        self.fallback().ok_or(err)
+    }
+
+    /// Fallbacks the total data into [`Ok`], or returns [`Err`] computed from `err`.
+    pub fn ok_or_else<E>(self, err: impl FnOnce() -> E) -> Result<T, E> {
+        self.fallback().ok_or_else(err)
+    }
+
+    /// Fallbacks the total data and maps it with `f`, or returns `default`.
+    pub fn map_or<V>(self, default: V, f: impl FnOnce(T) -> V) -> V {
+        self.fallback().map_or(default, f)
+    }
+
+    /// Returns the layer which [`Fallback::fallback`] chooses.
+    pub const fn source(&self) -> Option<Source> {
+        if self.data.is_some() {
+            Some(Source::Data)
+        } else if self.base_data.is_some() {
+            Some(Source::Base)
+        } else {
+            None
+        }
+    }
+
+    /// Fallbacks the data or part of data, and reports the layer it comes from.
+    pub fn and_then_with_source<V>(self, mut f: impl FnMut(T) -> Option<V>) -> Option<(V, Source)> {
+        self.data
+            .and_then(&mut f)
+            .map(|v| (v, Source::Data))
+            .or_else(|| self.base_data.and_then(&mut f).map(|v| (v, Source::Base)))
+    }
+
+    /// Fallbacks the total data, and reports the layer it comes from.
+    pub fn fallback_with_source(self) -> Option<(T, Source)> {
+        self.and_then_with_source(Some)
+    }
+
     /// Maps to a new [`Fallback`].
     pub fn map<V>(self, mut f: impl FnMut(T) -> V) -> Fallback<V> {
         Fallback::new(self.data.map(&mut f), self.base_data.map(&mut f))
@@ -70,6 +400,60 @@ impl<T> Fallback<T> {
     pub fn unzip(self) -> (Option<T>, Option<T>) {
         (self.data, self.base_data)
     }
+
+    /// Merges `data` with `base_data` by `f`, or fallbacks if either is [`None`].
+    pub fn merge_with(self, f: impl FnOnce(T, T) -> T) -> Option<T> {
+        match (self.data, self.base_data) {
+            (Some(data), Some(base_data)) => Some(f(data, base_data)),
+            (data, base_data) => data.or(base_data),
+        }
+    }
+}
+
+impl<T: Merge> Fallback<T> {
+    /// Merges `data` with `base_data`, or fallbacks if either is [`None`].
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// # use fallback::Fallback;
+    /// let fallback = Fallback::new(Some("world".to_string()), Some("hello ".to_string()));
+    /// assert_eq!(fallback.merge(), Some("hello world".to_string()));
+    /// ```
+    pub fn merge(self) -> Option<T> {
+        self.merge_with(Merge::merge)
+    }
+}
+
+/// The layer of a [`Fallback`] which supplies a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum Source {
+    /// The value comes from `data`.
+    Data,
+    /// The value comes from `base_data`.
+    Base,
+}
+
+impl core::fmt::Display for Source {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Data => f.write_str("data"),
+            Self::Base => f.write_str("base"),
+        }
+    }
+}
+
+impl<T: Copy> Fallback<&T> {
+    /// Converts from `Fallback<&T>` to `Fallback<T>` by copying.
+    pub fn copied(self) -> Fallback<T> {
+        Fallback::new(self.data.copied(), self.base_data.copied())
+    }
+}
+
+impl<T: Clone> Fallback<&T> {
+    /// Converts from `Fallback<&T>` to `Fallback<T>` by cloning.
+    pub fn cloned(self) -> Fallback<T> {
+        Fallback::new(self.data.cloned(), self.base_data.cloned())
+    }
 }
 
 impl<T> Fallback<Option<T>> {
@@ -103,6 +487,12 @@ impl<T: AsRef<str>> Fallback<T> {
     }
 }
 
+impl<T> Default for Fallback<T> {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
 impl<T> From<Fallback<T>> for Option<T> {
     fn from(f: Fallback<T>) -> Self {
         if f.data.is_some() {
@@ -113,7 +503,16 @@ impl<T> From<Fallback<T>> for Option<T> {
     }
 }
 
-#[doc(hidden)]
+/// An iterator zipping `data` and `base_data` by position,
+/// created by [`Fallback::into_iter`] and [`Fallback::positional_overlay`].
+///
+/// The items present in `data` overlay the items at the same position of `base_data`,
+/// and the longer layer fills in the rest.
+/// See [`Fallback::chain`] and [`Fallback::first_nonempty`] for the other iteration modes.
+#[cfg_attr(
+    feature = "std",
+    doc = "[`Fallback::dedup_union`] also skips the duplicated items."
+)]
 pub struct FallbackIter<A> {
     data: Option<A>,
     base_data: Option<A>,
@@ -136,7 +535,7 @@ impl<A: Iterator> Iterator for FallbackIter<A> {
 impl<T: IntoIterator> IntoIterator for Fallback<T> {
     type Item = Fallback<T::Item>;
 
-    type IntoIter = FallbackIter<std::iter::Fuse<T::IntoIter>>;
+    type IntoIter = FallbackIter<core::iter::Fuse<T::IntoIter>>;
 
     fn into_iter(self) -> Self::IntoIter {
         FallbackIter {
@@ -192,6 +591,126 @@ impl<T: IntoIterator> IntoIterator for Fallback<T> {
 ///     }
 /// }
 /// ```
+///
+/// The derived spec type can be resolved back into the original struct:
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// struct Foo {
+///     data1: i32,
+///     data2: String,
+/// }
+///
+/// let data = Fallback::new(None, Some(Foo { data1: 123, data2: "Hello".to_string() }));
+/// let data = data.spec().resolve().unwrap();
+/// assert_eq!(data.data1, 123);
+/// assert_eq!(data.data2, "Hello");
+/// ```
+///
+/// A field marked with `#[fallback(nested)]` uses the spec type of its own type,
+/// so that the fallback recurses into it field by field:
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// struct Inner {
+///     data1: i32,
+///     data2: String,
+/// }
+///
+/// #[derive(FallbackSpec)]
+/// struct Outer {
+///     #[fallback(nested)]
+///     inner: Inner,
+/// }
+///
+/// let data = Outer { inner: Inner { data1: 123, data2: String::new() } };
+/// let base_data = Outer { inner: Inner { data1: 456, data2: "Hello".to_string() } };
+/// let data = Fallback::new(Some(data), Some(base_data)).spec();
+/// assert_eq!(data.inner.data1.fallback(), Some(123));
+/// assert_eq!(data.inner.data2.and_any_str(), Some("Hello".to_string()));
+/// ```
+///
+/// A field marked with `#[fallback(data_only)]` or `#[fallback(base_only)]` is taken
+/// only from the data layer or the base layer, i.e., the first or the last layer of a chain.
+/// A field marked with `#[fallback(skip)]` is left out of the spec types,
+/// and resolved to its default value,
+/// so a generic parameter used only by skipped fields is rejected:
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// struct Foo {
+///     #[fallback(data_only)]
+///     token: String,
+///     #[fallback(base_only)]
+///     id: u32,
+///     #[fallback(skip)]
+///     cache: Vec<u8>,
+/// }
+///
+/// let data = Foo { token: "data".to_string(), id: 1, cache: vec![1] };
+/// let base_data = Foo { token: "base".to_string(), id: 2, cache: vec![2] };
+/// let data = Fallback::new(Some(data), Some(base_data)).spec().resolve().unwrap();
+/// assert_eq!(data.token, "data");
+/// assert_eq!(data.id, 2);
+/// assert!(data.cache.is_empty());
+/// ```
+///
+/// A field marked with `#[fallback(empty_is_none)]` skips the layers holding an empty value,
+/// see [`IsEmpty`], and a field marked with `#[fallback(when = "path")]` skips the layers
+/// failing the predicate `fn(&T) -> bool`.
+///
+/// A field marked with `#[fallback(merge)]` merges the layers with [`Merge`] instead of replacing.
+/// `#[fallback(merge = "append")]`, `"union"` and `"deep"` choose a [`MergeStrategy`](merge::MergeStrategy),
+/// and `#[fallback(merge = "path")]` calls `fn(T, T) -> T` with the higher-priority value first.
+///
+/// The generated code refers to this crate as `::fallback`.
+/// When it is re-exported by another crate, set the path with `#[fallback(crate = "path")]`:
+/// ```
+/// # pub extern crate fallback;
+/// mod facade {
+///     pub use fallback as inner;
+/// }
+///
+/// #[derive(facade::inner::FallbackSpec)]
+/// #[fallback(crate = "facade::inner")]
+/// struct Foo {
+///     data1: i32,
+/// }
+/// ```
+///
+/// The spec types are hidden, and named `__Fallback{Name}` and `__FallbackChain{Name}`.
+/// They are documented once named with `#[fallback(spec = "Name")]` and `#[fallback(chain_spec = "Name")]`.
+/// `#[fallback(spec_vis = "pub(crate)")]` sets their visibility, which can't be narrower than the type's own,
+/// `#[fallback(spec_docs)]` forwards the doc comments of the fields,
+/// and `#[fallback(spec_derive(...))]` derives extra traits for them.
+///
+/// The spec types and the partial type implement `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq`,
+/// `PartialOrd`, `Ord` and `Hash` whenever their fields do, and so do the spec structs with `Default`:
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// #[fallback(spec = "FooLayers", spec_docs)]
+/// pub struct Foo {
+///     /// The first data.
+///     pub data1: i32,
+/// }
+///
+/// fn layers(data: Foo, base_data: Foo) -> FooLayers {
+///     Fallback::new(Some(data), Some(base_data)).spec()
+/// }
+///
+/// let data = layers(Foo { data1: 123 }, Foo { data1: 456 });
+/// assert_eq!(data.data1, Fallback::new(Some(123), Some(456)));
+/// assert!(FooLayers::default() < data);
+/// ```
+///
+/// The spec struct has an `entry_{field}` method for each field which is not nested,
+/// which gets an [`Entry`] to edit the field in place.
+/// The fields marked with `base_only` have no entry, as they can't be overridden.
+///
+/// The generated code refers to `::core` paths only, and works in `no_std` crates.
+/// Without the `alloc` feature, the chain spec types, `provenance` and `present_fields`
+/// are left out, and [`MissingFields`] only counts the missing fields.
 pub trait FallbackSpec: Sized {
     /// The specialized fallback type.
     type SpecType: From<Fallback<Self>>;
@@ -204,9 +723,155 @@ impl<T: FallbackSpec> Fallback<T> {
     }
 }
 
+/// The error when some fields are [`None`] in every layer,
+/// and the spec type can't be resolved into the original type.
+///
+/// The names of nested fields are joined by `.`,
+/// and an empty name stands for the value itself, e.g. an enum without any layer.
+/// Without the `alloc` feature, only the numbers of the fields are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MissingFields {
+    #[cfg(feature = "alloc")]
+    fields: Vec<String>,
+    #[cfg(feature = "alloc")]
+    conflicts: Vec<String>,
+    #[cfg(not(feature = "alloc"))]
+    fields: usize,
+    #[cfg(not(feature = "alloc"))]
+    conflicts: usize,
+}
+
+impl MissingFields {
+    /// Creates a new [`MissingFields`] with the names of the missing fields.
+    #[cfg(feature = "alloc")]
+    pub fn new(fields: Vec<String>) -> Self {
+        Self {
+            fields,
+            conflicts: vec![],
+        }
+    }
+
+    /// Creates a new [`MissingFields`] for an enum whose layers hold different variants.
+    pub fn conflict() -> Self {
+        Self {
+            #[cfg(feature = "alloc")]
+            conflicts: vec![String::new()],
+            #[cfg(not(feature = "alloc"))]
+            conflicts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// The names of the missing fields.
+    #[cfg(feature = "alloc")]
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// The names of the enum fields whose layers hold different variants.
+    #[cfg(feature = "alloc")]
+    pub fn conflicts(&self) -> &[String] {
+        &self.conflicts
+    }
+
+    /// Exacts the names of the missing fields.
+    #[cfg(feature = "alloc")]
+    pub fn into_fields(self) -> Vec<String> {
+        self.fields
+    }
+
+    /// Returns `true` if nothing is missing or conflicting.
+    #[cfg(feature = "alloc")]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.conflicts.is_empty()
+    }
+
+    /// Returns `true` if nothing is missing or conflicting.
+    #[cfg(not(feature = "alloc"))]
+    pub fn is_empty(&self) -> bool {
+        self.fields == 0 && self.conflicts == 0
+    }
+
+    /// Adds a missing field.
+    #[cfg(feature = "alloc")]
+    pub fn push(&mut self, field: impl Into<String>) {
+        self.fields.push(field.into());
+    }
+
+    /// Adds a missing field.
+    #[cfg(not(feature = "alloc"))]
+    pub fn push(&mut self, _field: &str) {
+        self.fields += 1;
+    }
+
+    /// Adds the missing and conflicting fields of a nested field called `name`.
+    #[cfg(feature = "alloc")]
+    pub fn extend_nested(&mut self, name: &str, nested: Self) {
+        self.fields.extend(
+            nested
+                .fields
+                .iter()
+                .map(|field| __private::join_field(name, field)),
+        );
+        self.conflicts.extend(
+            nested
+                .conflicts
+                .iter()
+                .map(|field| __private::join_field(name, field)),
+        );
+    }
+
+    /// Adds the missing and conflicting fields of a nested field called `name`.
+    #[cfg(not(feature = "alloc"))]
+    pub fn extend_nested(&mut self, _name: &str, nested: Self) {
+        self.fields += nested.fields;
+        self.conflicts += nested.conflicts;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for MissingFields {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fn names(fields: &[String]) -> String {
+            fields
+                .iter()
+                .map(|field| if field.is_empty() { "self" } else { field })
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        let mut parts = vec![];
+        if !self.fields.is_empty() {
+            parts.push(format!("missing fields: {}", names(&self.fields)));
+        }
+        if !self.conflicts.is_empty() {
+            parts.push(format!("conflicting variants: {}", names(&self.conflicts)));
+        }
+        f.write_str(&parts.join("; "))
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+impl core::fmt::Display for MissingFields {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.fields > 0 {
+            write!(f, "{} missing fields", self.fields)?;
+        }
+        if self.fields > 0 && self.conflicts > 0 {
+            f.write_str("; ")?;
+        }
+        if self.conflicts > 0 {
+            write!(f, "{} conflicting variants", self.conflicts)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for MissingFields {}
+
 pub use fallback_derive::FallbackSpec;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use crate::*;
 
@@ -221,6 +886,70 @@ mod test {
         assert_eq!(Option::from(f), Some(100));
     }
 
+    #[test]
+    fn combinators() {
+        let mut f = Fallback::new(Some("".to_string()), Some("base".to_string()));
+        assert!(f.is_data() && !f.is_base_only() && !f.is_none());
+        assert_eq!(f.as_deref().and_any_str(), Some("base"));
+        assert_eq!(f.as_ref().cloned().unzip(), f.clone().unzip());
+        f.as_deref_mut().map(|s| s.make_ascii_uppercase());
+        assert_eq!(
+            f.as_ref().filter(|s| !s.is_empty()).fallback().unwrap(),
+            "BASE"
+        );
+        assert_eq!(f.replace("data".to_string()), Some(String::new()));
+
+        let mut f = Fallback::new(None, Some(1));
+        *f.set_data(2) += 1;
+        assert_eq!(f.clear_data(), Some(3));
+        assert_eq!(*f.promote().unwrap(), 1);
+        *f.base_mut() = None;
+        assert_eq!(f.source(), Some(Source::Data));
+        *f.data_mut() = None;
+        assert_eq!(f.promote(), None);
+        f.set_base(4);
+        assert_eq!(f.fallback(), Some(4));
+
+        let f = Fallback::new(None, Some(1)).zip(Fallback::new(Some(2), Some(3)));
+        assert!(f.is_base_only());
+        assert_eq!(f.as_ref().copied().fallback(), Some((1, 3)));
+        assert_eq!(f.map_or(0, |(a, b)| a + b), 4);
+
+        let mut f = Fallback::new(Some(1), None);
+        assert_eq!(f.take().unwrap_or(0), 1);
+        assert!(f.is_none());
+        assert_eq!(f.unwrap_or_default(), 0);
+        assert_eq!(
+            Fallback::<i32>::new(None, None).ok_or("missing"),
+            Err("missing")
+        );
+    }
+
+    #[test]
+    fn std_traits() {
+        let f = Fallback::new(Some(1), None);
+        let copy = f;
+        assert_eq!(f, copy);
+        assert_ne!(f, Fallback::new(None, Some(1)));
+        assert!(Fallback::new(None, Some(2)) < f);
+        assert_eq!(Fallback::<i32>::default(), Fallback::new(None, None));
+        assert_eq!(
+            std::collections::HashSet::from([f, copy, Fallback::default()]).len(),
+            2
+        );
+        assert_eq!(FallbackChain::<i32>::default(), FallbackChain::new(vec![]));
+    }
+
+    #[test]
+    fn source() {
+        assert_eq!(Fallback::<()>::new(None, None).source(), None);
+        assert_eq!(Fallback::new(Some(1), Some(2)).source(), Some(Source::Data));
+        assert_eq!(
+            Fallback::new(None, Some(2)).fallback_with_source(),
+            Some((2, Source::Base))
+        );
+    }
+
     #[test]
     fn empty() {
         let f = Fallback::new(Some(vec![]), Some(vec![1, 1, 4, 5, 1, 4]));
@@ -230,6 +959,23 @@ mod test {
         assert_eq!(f.and_any_str(), Some("Hello world!".to_string()));
     }
 
+    #[test]
+    fn merge() {
+        let f = Fallback::new(Some(vec![3, 2, 1]), Some(vec![1, 1, 4]));
+        assert_eq!(f.merge(), Some(vec![1, 1, 4, 3, 2, 1]));
+
+        let f = Fallback::new(
+            Some(std::collections::HashMap::from([("a", 1), ("b", 2)])),
+            Some(std::collections::HashMap::from([("b", 3), ("c", 4)])),
+        );
+        let merged = f.merge().unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged["b"], 2);
+
+        let f = Fallback::new(None, Some(vec![1]));
+        assert_eq!(f.merge(), Some(vec![1]));
+    }
+
     #[test]
     fn iter() {
         let f = Fallback::new(Some(vec![3, 2, 1]), Some(vec![1, 1, 4, 5, 1, 4]));
@@ -241,3 +987,20 @@ mod test {
         );
     }
 }
+
+#[cfg(all(test, not(feature = "alloc")))]
+mod core_test {
+    use crate::*;
+
+    #[test]
+    fn missing_fields() {
+        let mut missing = MissingFields::default();
+        assert!(missing.is_empty());
+        missing.extend_nested("inner", MissingFields::default());
+        assert!(missing.is_empty());
+        missing.push("data1");
+        missing.extend_nested("inner", MissingFields::conflict());
+        assert!(!missing.is_empty());
+        assert_ne!(missing, MissingFields::conflict());
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{Fallback, Source};
+use alloc::collections::{btree_map, BTreeMap};
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+use core::{borrow::Borrow, cmp::Ordering, iter::Peekable};
+#[cfg(feature = "std")]
+use std::collections::{hash_map, HashMap};
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher> Fallback<HashMap<K, V, S>> {
+    /// Looks up `key` in `data`, and then in `base_data`.
+    /// ```
+    /// # use fallback::Fallback;
+    /// # use std::collections::HashMap;
+    /// let data = HashMap::from([("hello", "你好")]);
+    /// let base_data = HashMap::from([("hello", "hello"), ("world", "world")]);
+    /// let fallback = Fallback::new(Some(data), Some(base_data));
+    /// assert_eq!(fallback.get("hello"), Some(&"你好"));
+    /// assert_eq!(fallback.get("world"), Some(&"world"));
+    /// assert_eq!(fallback.get("foo"), None);
+    /// ```
+    pub fn get<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_with_source(key).map(|(value, _)| value)
+    }
+
+    /// Looks up `key` in `data`, and then in `base_data`,
+    /// and reports the layer it comes from.
+    pub fn get_with_source<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<(&V, Source)>
+    where
+        K: Borrow<Q>,
+    {
+        self.as_ref().and_then_with_source(|map| map.get(key))
+    }
+
+    /// Returns `true` if either layer contains `key`.
+    pub fn contains_key<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Iterates the union of the keys of both layers, in arbitrary order,
+    /// with the values of the same key wrapped in a [`Fallback`].
+    /// ```
+    /// # use fallback::Fallback;
+    /// # use std::collections::HashMap;
+    /// let data = HashMap::from([("Accept", "text/html")]);
+    /// let base_data = HashMap::from([("Accept", "*/*"), ("User-Agent", "fallback")]);
+    /// let fallback = Fallback::new(Some(data), Some(base_data));
+    /// let headers = fallback
+    ///     .into_key_union()
+    ///     .map(|(key, value)| (key, value.fallback().unwrap()))
+    ///     .collect::<HashMap<_, _>>();
+    /// assert_eq!(headers["Accept"], "text/html");
+    /// assert_eq!(headers["User-Agent"], "fallback");
+    /// ```
+    pub fn into_key_union(self) -> HashMapKeyUnion<K, V, S>
+    where
+        S: Default,
+    {
+        let (data, base_data) = self.unzip();
+        HashMapKeyUnion {
+            data: data.unwrap_or_default().into_iter(),
+            base_data: base_data.unwrap_or_default(),
+            base_rest: None,
+        }
+    }
+}
+
+/// An iterator over the union of the keys of a `Fallback<HashMap<K, V, S>>`,
+/// created by [`Fallback::into_key_union`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct HashMapKeyUnion<K, V, S> {
+    data: hash_map::IntoIter<K, V>,
+    base_data: HashMap<K, V, S>,
+    base_rest: Option<hash_map::IntoIter<K, V>>,
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher + Default> Iterator for HashMapKeyUnion<K, V, S> {
+    type Item = (K, Fallback<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((key, value)) = self.data.next() {
+            let base_value = self.base_data.remove(&key);
+            return Some((key, Fallback::new(Some(value), base_value)));
+        }
+        self.base_rest
+            .get_or_insert_with(|| core::mem::take(&mut self.base_data).into_iter())
+            .next()
+            .map(|(key, value)| (key, Fallback::new(None, Some(value))))
+    }
+}
+
+impl<K: Ord, V> Fallback<BTreeMap<K, V>> {
+    /// Looks up `key` in `data`, and then in `base_data`.
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_with_source(key).map(|(value, _)| value)
+    }
+
+    /// Looks up `key` in `data`, and then in `base_data`,
+    /// and reports the layer it comes from.
+    pub fn get_with_source<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&V, Source)>
+    where
+        K: Borrow<Q>,
+    {
+        self.as_ref().and_then_with_source(|map| map.get(key))
+    }
+
+    /// Returns `true` if either layer contains `key`.
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Iterates the union of the keys of both layers, in ascending order,
+    /// with the values of the same key wrapped in a [`Fallback`].
+    /// ```
+    /// # use fallback::Fallback;
+    /// # use std::collections::BTreeMap;
+    /// let data = BTreeMap::from([("b", 2), ("c", 3)]);
+    /// let base_data = BTreeMap::from([("a", 10), ("b", 20)]);
+    /// let fallback = Fallback::new(Some(data), Some(base_data));
+    /// let values = fallback
+    ///     .into_key_union()
+    ///     .map(|(key, value)| (key, value.fallback().unwrap()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(values, [("a", 10), ("b", 2), ("c", 3)]);
+    /// ```
+    pub fn into_key_union(self) -> BTreeMapKeyUnion<K, V> {
+        let (data, base_data) = self.unzip();
+        BTreeMapKeyUnion {
+            data: data.unwrap_or_default().into_iter().peekable(),
+            base_data: base_data.unwrap_or_default().into_iter().peekable(),
+        }
+    }
+}
+
+/// An iterator over the union of the keys of a `Fallback<BTreeMap<K, V>>` in ascending order,
+/// created by [`Fallback::into_key_union`].
+#[derive(Debug)]
+pub struct BTreeMapKeyUnion<K, V> {
+    data: Peekable<btree_map::IntoIter<K, V>>,
+    base_data: Peekable<btree_map::IntoIter<K, V>>,
+}
+
+impl<K: Ord, V> Iterator for BTreeMapKeyUnion<K, V> {
+    type Item = (K, Fallback<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let order = match (self.data.peek(), self.base_data.peek()) {
+            (Some((key, _)), Some((base_key, _))) => key.cmp(base_key),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        match order {
+            Ordering::Less => {
+                let (key, value) = self.data.next()?;
+                Some((key, Fallback::new(Some(value), None)))
+            }
+            Ordering::Greater => {
+                let (key, value) = self.base_data.next()?;
+                Some((key, Fallback::new(None, Some(value))))
+            }
+            Ordering::Equal => {
+                let (key, value) = self.data.next()?;
+                let (_, base_value) = self.base_data.next()?;
+                Some((key, Fallback::new(Some(value), Some(base_value))))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn hash_map() {
+        let f = Fallback::new(
+            Some(HashMap::from([("a", 1), ("b", 2)])),
+            Some(HashMap::from([("b", 20), ("c", 30)])),
+        );
+        assert_eq!(f.get_with_source("b"), Some((&2, Source::Data)));
+        assert_eq!(f.get_with_source("c"), Some((&30, Source::Base)));
+        assert!(!f.contains_key("d"));
+
+        let mut union = f
+            .into_key_union()
+            .map(|(key, value)| (key, value.unzip()))
+            .collect::<Vec<_>>();
+        union.sort();
+        assert_eq!(
+            union,
+            [
+                ("a", (Some(1), None)),
+                ("b", (Some(2), Some(20))),
+                ("c", (None, Some(30)))
+            ]
+        );
+
+        let f = Fallback::<HashMap<i32, i32>>::new(None, Some(HashMap::from([(1, 1)])));
+        assert_eq!(f.into_key_union().count(), 1);
+    }
+
+    #[test]
+    fn btree_map() {
+        let f = Fallback::new(
+            Some(BTreeMap::from([(3, "c"), (1, "a")])),
+            Some(BTreeMap::from([(4, "D"), (2, "B"), (1, "A")])),
+        );
+        assert_eq!(f.get(&1), Some(&"a"));
+        assert_eq!(f.get(&2), Some(&"B"));
+        assert_eq!(
+            f.into_key_union()
+                .map(|(key, value)| (key, value.fallback().unwrap()))
+                .collect::<Vec<_>>(),
+            [(1, "a"), (2, "B"), (3, "c"), (4, "D")]
+        );
+    }
+}
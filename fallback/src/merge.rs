@@ -0,0 +1,223 @@
+//! Merging the layers instead of replacing.
+
+#[cfg(feature = "alloc")]
+use alloc::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    string::String,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+/// A value which can be merged with a value of lower priority,
+/// used by [`Fallback::merge`](crate::Fallback::merge)
+/// and a field marked with `#[fallback(merge)]`.
+///
+/// Lists and strings are appended, sets are united,
+/// and maps are united key by key, where `self` takes priority.
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// # use fallback::*;
+/// let data = Fallback::new(Some(vec![3, 4]), Some(vec![1, 2]));
+/// assert_eq!(data.merge(), Some(vec![1, 2, 3, 4]));
+/// ```
+pub trait Merge {
+    /// Merges `self` with the lower-priority `base`.
+    fn merge(self, base: Self) -> Self;
+}
+
+/// A strategy to merge a value with a value of lower priority,
+/// used by a field marked with `#[fallback(merge = "...")]`.
+///
+/// The strategies are [`Append`], [`Union`] and [`Deep`]:
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// # use fallback::*;
+/// # use std::collections::{BTreeMap, BTreeSet};
+/// #[derive(FallbackSpec)]
+/// struct Foo {
+///     #[fallback(merge = "append")]
+///     plugins: Vec<String>,
+///     #[fallback(merge = "union")]
+///     features: BTreeSet<String>,
+///     #[fallback(merge = "deep")]
+///     env: BTreeMap<String, Vec<String>>,
+/// }
+///
+/// let data = Foo {
+///     plugins: vec!["data".to_string()],
+///     features: BTreeSet::from(["a".to_string()]),
+///     env: BTreeMap::from([("PATH".to_string(), vec!["/data".to_string()])]),
+/// };
+/// let base_data = Foo {
+///     plugins: vec!["base".to_string()],
+///     features: BTreeSet::from(["b".to_string()]),
+///     env: BTreeMap::from([("PATH".to_string(), vec!["/base".to_string()])]),
+/// };
+/// let data = Fallback::new(Some(data), Some(base_data)).spec().resolve().unwrap();
+/// assert_eq!(data.plugins, ["base", "data"]);
+/// assert_eq!(data.features.len(), 2);
+/// assert_eq!(data.env["PATH"], ["/base", "/data"]);
+/// ```
+pub trait MergeStrategy<T> {
+    /// Merges `data` with the lower-priority `base`.
+    fn merge(data: T, base: T) -> T;
+}
+
+/// Appends `data` to `base`.
+#[derive(Debug, Clone, Copy)]
+pub struct Append;
+
+/// Unites the sets, or the maps key by key, where `data` takes priority.
+#[derive(Debug, Clone, Copy)]
+pub struct Union;
+
+/// Unites the maps key by key, and merges the values of the same key with [`Merge`].
+#[derive(Debug, Clone, Copy)]
+pub struct Deep;
+
+#[cfg(feature = "alloc")]
+impl<T> MergeStrategy<Vec<T>> for Append {
+    fn merge(mut data: Vec<T>, mut base: Vec<T>) -> Vec<T> {
+        base.append(&mut data);
+        base
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> MergeStrategy<VecDeque<T>> for Append {
+    fn merge(mut data: VecDeque<T>, mut base: VecDeque<T>) -> VecDeque<T> {
+        base.append(&mut data);
+        base
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl MergeStrategy<String> for Append {
+    fn merge(data: String, mut base: String) -> String {
+        base.push_str(&data);
+        base
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Eq + Hash, S: BuildHasher> MergeStrategy<HashSet<T, S>> for Union {
+    fn merge(mut data: HashSet<T, S>, base: HashSet<T, S>) -> HashSet<T, S> {
+        data.extend(base);
+        data
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Ord> MergeStrategy<BTreeSet<T>> for Union {
+    fn merge(mut data: BTreeSet<T>, mut base: BTreeSet<T>) -> BTreeSet<T> {
+        data.append(&mut base);
+        data
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher> MergeStrategy<HashMap<K, V, S>> for Union {
+    fn merge(mut data: HashMap<K, V, S>, base: HashMap<K, V, S>) -> HashMap<K, V, S> {
+        for (key, value) in base {
+            data.entry(key).or_insert(value);
+        }
+        data
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, V> MergeStrategy<BTreeMap<K, V>> for Union {
+    fn merge(mut data: BTreeMap<K, V>, base: BTreeMap<K, V>) -> BTreeMap<K, V> {
+        for (key, value) in base {
+            data.entry(key).or_insert(value);
+        }
+        data
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V: Merge, S: BuildHasher> MergeStrategy<HashMap<K, V, S>> for Deep {
+    fn merge(mut data: HashMap<K, V, S>, base: HashMap<K, V, S>) -> HashMap<K, V, S> {
+        for (key, value) in base {
+            match data.remove(&key) {
+                Some(data_value) => data.insert(key, data_value.merge(value)),
+                None => data.insert(key, value),
+            };
+        }
+        data
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, V: Merge> MergeStrategy<BTreeMap<K, V>> for Deep {
+    fn merge(mut data: BTreeMap<K, V>, base: BTreeMap<K, V>) -> BTreeMap<K, V> {
+        for (key, value) in base {
+            match data.remove(&key) {
+                Some(data_value) => data.insert(key, data_value.merge(value)),
+                None => data.insert(key, value),
+            };
+        }
+        data
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Merge for Vec<T> {
+    fn merge(self, base: Self) -> Self {
+        Append::merge(self, base)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Merge for VecDeque<T> {
+    fn merge(self, base: Self) -> Self {
+        Append::merge(self, base)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Merge for String {
+    fn merge(self, base: Self) -> Self {
+        Append::merge(self, base)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Eq + Hash, S: BuildHasher> Merge for HashSet<T, S> {
+    fn merge(self, base: Self) -> Self {
+        Union::merge(self, base)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Ord> Merge for BTreeSet<T> {
+    fn merge(self, base: Self) -> Self {
+        Union::merge(self, base)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher> Merge for HashMap<K, V, S> {
+    fn merge(self, base: Self) -> Self {
+        Union::merge(self, base)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, V> Merge for BTreeMap<K, V> {
+    fn merge(self, base: Self) -> Self {
+        Union::merge(self, base)
+    }
+}
+
+impl<T: Merge> Merge for Option<T> {
+    fn merge(self, base: Self) -> Self {
+        match (self, base) {
+            (Some(data), Some(base)) => Some(data.merge(base)),
+            (data, base) => data.or(base),
+        }
+    }
+}
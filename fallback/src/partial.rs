@@ -0,0 +1,63 @@
+use crate::{FallbackSpec, MissingFields};
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+/// A sparse layer of a struct, where every field is optional.
+///
+/// It is implemented by `#[derive(FallbackSpec)]` with `#[fallback(partial)]`,
+/// which generates a `Partial{Name}` type, or `#[fallback(partial = "Name")]` to pick the name.
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// #[fallback(partial)]
+/// struct Foo {
+///     data1: i32,
+///     data2: String,
+/// }
+///
+/// let user = PartialFoo { data1: Some(123), ..Default::default() };
+/// let project = PartialFoo { data1: Some(456), data2: None };
+/// let base = Foo { data1: 0, data2: "Hello".to_string() };
+///
+/// let data = PartialFoo::from_layers([user, project]).resolve(Some(base)).unwrap();
+/// assert_eq!(data.data1, 123);
+/// assert_eq!(data.data2, "Hello");
+/// ```
+///
+/// A field marked with `#[fallback(base_only)]` is taken from the full base if any,
+/// or from the last layer holding it otherwise, as that layer is the base of the others.
+pub trait Partial: Default {
+    /// The full type.
+    type Full: FallbackSpec;
+
+    /// Fills the absent fields with the ones in `base`.
+    fn or(self, base: Self) -> Self;
+
+    /// Stacks the layers, ordered from the highest priority to the lowest.
+    fn from_layers(layers: impl IntoIterator<Item = Self>) -> Self {
+        layers.into_iter().fold(Self::default(), Self::or)
+    }
+
+    /// Builds the specialized fallback object, with an optional full base.
+    fn spec(self, base: Option<Self::Full>) -> <Self::Full as FallbackSpec>::SpecType;
+
+    /// Fallbacks every field to the optional full base, and collects them into the full type.
+    fn resolve(self, base: Option<Self::Full>) -> Result<Self::Full, MissingFields>;
+
+    /// The names of the fields which are present, with the nested ones joined by `.`.
+    /// The fields marked with `base_only` are left out, see [`Partial::present_base_fields`].
+    #[cfg(feature = "alloc")]
+    fn present_fields(&self) -> Vec<String>;
+
+    /// The names of the fields marked with `base_only` which are present,
+    /// with the nested ones joined by `.`.
+    /// They are taken from the last layer holding them, and from the full base if any.
+    #[cfg(feature = "alloc")]
+    fn present_base_fields(&self) -> Vec<String>;
+}
+
+/// This trait links a struct to its partial type.
+pub trait FallbackPartial: FallbackSpec {
+    /// The partial type.
+    type Partial: Partial<Full = Self> + From<Self>;
+}
@@ -0,0 +1,57 @@
+//! Serde helpers for [`Fallback`](crate::Fallback).
+//!
+//! The `#[fallback(serde)]` attribute makes `#[derive(FallbackSpec)]` implement
+//! `Serialize` and `Deserialize` for the generated spec and partial types.
+//! The partial type deserializes from sparse documents, where a missing key is [`None`]:
+//! ```
+//! # use fallback::*;
+//! #[derive(FallbackSpec)]
+//! #[fallback(partial, serde)]
+//! struct Foo {
+//!     data1: i32,
+//!     data2: String,
+//! }
+//!
+//! let data: PartialFoo = serde_json::from_str(r#"{"data1": 123}"#).unwrap();
+//! assert_eq!(data.data1, Some(123));
+//! assert_eq!(data.data2, None);
+//! ```
+
+/// (De)serializes a [`Fallback`](crate::Fallback) as its resolved value.
+///
+/// It is used with `#[serde(with = "fallback::serde_impl::resolved")]`.
+/// The value is serialized as [`Fallback::fallback`](crate::Fallback::fallback) does,
+/// and deserialized into `data`, with an empty `base_data`.
+/// ```
+/// # use fallback::Fallback;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Foo {
+///     #[serde(with = "fallback::serde_impl::resolved")]
+///     data: Fallback<i32>,
+/// }
+///
+/// let foo = Foo { data: Fallback::new(None, Some(123)) };
+/// assert_eq!(serde_json::to_string(&foo).unwrap(), r#"{"data":123}"#);
+///
+/// let foo: Foo = serde_json::from_str(r#"{"data":456}"#).unwrap();
+/// assert_eq!(foo.data.unzip(), (Some(456), None));
+/// ```
+pub mod resolved {
+    use crate::Fallback;
+    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes the resolved value.
+    pub fn serialize<T: Serialize, S: Serializer>(
+        data: &Fallback<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        data.as_ref().fallback().serialize(serializer)
+    }
+
+    /// Deserializes the value into `data`.
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Fallback<T>, D::Error> {
+        Option::deserialize(deserializer).map(|data| Fallback::new(data, None))
+    }
+}
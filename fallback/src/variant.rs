@@ -0,0 +1,184 @@
+#[cfg(feature = "alloc")]
+use crate::FallbackChain;
+use crate::{Fallback, MissingFields, Source};
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
+/// The specialized fallback type of an enum.
+///
+/// The fallback happens at the variant level: the variant of the first existing layer is chosen,
+/// and the fields of the layers holding the same variant fallback field by field.
+/// The layers holding other variants are ignored by default,
+/// or treated as a conflict with `#[fallback(on_conflict = "conflict")]`.
+/// ```
+/// # use fallback::*;
+/// #[derive(FallbackSpec)]
+/// enum Proto {
+///     Tcp { port: Option<u16>, nodelay: bool },
+///     Udp(u16),
+/// }
+///
+/// let data = Proto::Tcp { port: None, nodelay: true };
+/// let base_data = Proto::Tcp { port: Some(80), nodelay: false };
+/// let data = Fallback::new(Some(data), Some(base_data)).spec();
+/// match data {
+///     VariantFallback::Variant(__FallbackProto::Tcp { port, nodelay }, source) => {
+///         assert_eq!(source, Source::Data);
+///         assert_eq!(port.flatten().fallback(), Some(80));
+///         assert_eq!(nodelay.fallback(), Some(true));
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum VariantFallback<T: FallbackVariants> {
+    /// Both layers are [`None`].
+    None,
+    /// The chosen variant, with the fields wrapped, and the layer which supplies the variant.
+    Variant(T::Variants, Source),
+    /// The layers hold different variants, and `#[fallback(on_conflict = "conflict")]` is set.
+    Conflict {
+        /// The `data` layer.
+        data: T,
+        /// The `base_data` layer.
+        base: T,
+    },
+}
+
+// Derived `Default` would require `T: Default`.
+#[allow(clippy::derivable_impls)]
+impl<T: FallbackVariants> Default for VariantFallback<T> {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl<T: FallbackVariants> VariantFallback<T> {
+    /// Reports the layer which supplies the variant, with an empty name, and each field.
+    #[cfg(feature = "alloc")]
+    pub fn provenance(&self) -> Vec<(String, Option<Source>)> {
+        match self {
+            Self::Variant(variants, source) => {
+                let mut provenance = vec![(String::new(), Some(*source))];
+                provenance.extend(T::provenance(variants));
+                provenance
+            }
+            _ => vec![(String::new(), None)],
+        }
+    }
+
+    /// Fallbacks every field of the chosen variant, and collects them into the original enum.
+    pub fn resolve(self) -> Result<T, MissingFields> {
+        match self {
+            Self::None => Err(missing_variant()),
+            Self::Variant(variants, _) => T::resolve(variants),
+            Self::Conflict { .. } => Err(MissingFields::conflict()),
+        }
+    }
+}
+
+impl<T: FallbackVariants> From<Fallback<T>> for VariantFallback<T> {
+    fn from(data: Fallback<T>) -> Self {
+        let (data, base_data) = data.unzip();
+        T::spec(data, base_data)
+    }
+}
+
+/// The error of an enum without any layer, where the value itself is missing.
+fn missing_variant() -> MissingFields {
+    let mut missing = MissingFields::default();
+    missing.push("");
+    missing
+}
+
+/// This trait helps to create the specialized fallback type of an enum.
+///
+/// It is implemented by `#[derive(FallbackSpec)]` on enums.
+pub trait FallbackVariants: Sized {
+    /// The enum mirroring the variants, with the fields wrapped in [`Fallback`].
+    type Variants;
+
+    /// Chooses the variant, and wraps the fields of the layers.
+    fn spec(data: Option<Self>, base_data: Option<Self>) -> VariantFallback<Self>;
+
+    /// Reports the layer which supplies each field of the variant.
+    #[cfg(feature = "alloc")]
+    fn provenance(variants: &Self::Variants) -> Vec<(String, Option<Source>)>;
+
+    /// Fallbacks every field of the variant, and collects them into the original enum.
+    fn resolve(variants: Self::Variants) -> Result<Self, MissingFields>;
+}
+
+/// The specialized fallback chain type of an enum.
+///
+/// It is the [`FallbackChain`] counterpart of [`VariantFallback`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum VariantFallbackChain<T: FallbackChainVariants> {
+    /// All layers are [`None`].
+    None,
+    /// The chosen variant, with the fields wrapped, and the index of the layer which supplies the variant.
+    Variant(T::ChainVariants, usize),
+    /// The layers hold different variants, and `#[fallback(on_conflict = "conflict")]` is set.
+    Conflict(Vec<Option<T>>),
+}
+
+// Derived `Default` would require `T: Default`.
+#[cfg(feature = "alloc")]
+#[allow(clippy::derivable_impls)]
+impl<T: FallbackChainVariants> Default for VariantFallbackChain<T> {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: FallbackChainVariants> VariantFallbackChain<T> {
+    /// Reports the index of the layer which supplies the variant, with an empty name, and each field.
+    pub fn provenance(&self) -> Vec<(String, Option<usize>)> {
+        match self {
+            Self::Variant(variants, layer) => {
+                let mut provenance = vec![(String::new(), Some(*layer))];
+                provenance.extend(T::provenance(variants));
+                provenance
+            }
+            _ => vec![(String::new(), None)],
+        }
+    }
+
+    /// Fallbacks every field of the chosen variant, and collects them into the original enum.
+    pub fn resolve(self) -> Result<T, MissingFields> {
+        match self {
+            Self::None => Err(missing_variant()),
+            Self::Variant(variants, _) => T::resolve(variants),
+            Self::Conflict(_) => Err(MissingFields::conflict()),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: FallbackChainVariants> From<FallbackChain<T>> for VariantFallbackChain<T> {
+    fn from(data: FallbackChain<T>) -> Self {
+        T::spec(data.into_layers())
+    }
+}
+
+/// This trait helps to create the specialized fallback chain type of an enum.
+///
+/// It is implemented by `#[derive(FallbackSpec)]` on enums.
+#[cfg(feature = "alloc")]
+pub trait FallbackChainVariants: Sized {
+    /// The enum mirroring the variants, with the fields wrapped in [`FallbackChain`].
+    type ChainVariants;
+
+    /// Chooses the variant, and wraps the fields of the layers.
+    fn spec(layers: Vec<Option<Self>>) -> VariantFallbackChain<Self>;
+
+    /// Reports the index of the layer which supplies each field of the variant.
+    fn provenance(variants: &Self::ChainVariants) -> Vec<(String, Option<usize>)>;
+
+    /// Fallbacks every field of the variant, and collects them into the original enum.
+    fn resolve(variants: Self::ChainVariants) -> Result<Self, MissingFields>;
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "config")]
+
+use fallback::{
+    config::{Config, ConfigError, Format},
+    FallbackSpec,
+};
+
+#[derive(FallbackSpec)]
+#[fallback(partial, serde)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial, serde)]
+struct Settings {
+    name: String,
+    #[fallback(nested)]
+    server: Server,
+}
+
+fn defaults() -> Settings {
+    Settings {
+        name: "app".to_string(),
+        server: Server {
+            host: "localhost".to_string(),
+            port: 80,
+        },
+    }
+}
+
+#[test]
+fn layers() {
+    std::env::set_var("FALLBACK_TEST_LAYERS_SERVER__PORT", "8080");
+    let loaded = Config::new()
+        .env("FALLBACK_TEST_LAYERS_")
+        .map([("server.host", "example.com")])
+        .str(Format::Json, r#"{"name": "json", "server": {"port": 1}}"#)
+        .defaults(defaults())
+        .load()
+        .unwrap();
+
+    assert_eq!(loaded.value.name, "json");
+    assert_eq!(loaded.value.server.host, "example.com");
+    assert_eq!(loaded.value.server.port, 8080);
+    assert_eq!(
+        loaded.provenance,
+        [
+            ("server.port".to_string(), 0),
+            ("server.host".to_string(), 1),
+            ("name".to_string(), 2),
+        ]
+    );
+    assert_eq!(
+        loaded.source_of("server.port"),
+        Some("env FALLBACK_TEST_LAYERS_*")
+    );
+}
+
+#[test]
+fn env() {
+    std::env::set_var("FALLBACK_TEST_ENV_NAME", "8080");
+    std::env::set_var("FALLBACK_TEST_ENV_SERVER__HOST", "null");
+    std::env::set_var("FALLBACK_TEST_ENV_SERVER__PORT", "8080");
+    let loaded = Config::<Settings>::new()
+        .env("FALLBACK_TEST_ENV_")
+        .load()
+        .unwrap();
+    assert_eq!(loaded.value.name, "8080");
+    assert_eq!(loaded.value.server.host, "null");
+    assert_eq!(loaded.value.server.port, 8080);
+
+    std::env::set_var("FALLBACK_TEST_JSON_ENV_NAME", "{}");
+    std::env::set_var(
+        "FALLBACK_TEST_JSON_ENV_SERVER",
+        r#"{"host": "json", "port": 1}"#,
+    );
+    let loaded = Config::<Settings>::new()
+        .env("FALLBACK_TEST_JSON_ENV_")
+        .load()
+        .unwrap();
+    assert_eq!(loaded.value.name, "{}");
+    assert_eq!(loaded.value.server.host, "json");
+    assert_eq!(loaded.value.server.port, 1);
+
+    std::env::set_var("FALLBACK_TEST_INVALID_ENV_SERVER__PORT", "eighty");
+    let err = Config::new()
+        .env("FALLBACK_TEST_INVALID_ENV_")
+        .defaults(defaults())
+        .load();
+    assert!(matches!(err, Err(ConfigError::Parse { .. })));
+}
+
+#[test]
+fn file() {
+    let path = std::env::temp_dir().join("fallback_test_file.json");
+    std::fs::write(&path, r#"{"server": {"host": "file"}}"#).unwrap();
+    let loaded = Config::new()
+        .file(&path)
+        .optional_file("not_exist.json")
+        .defaults(defaults())
+        .load()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.value.server.host, "file");
+    assert_eq!(loaded.sources.len(), 3);
+    assert_eq!(loaded.source_of("name"), Some("defaults"));
+
+    let err = Config::<Settings>::new().file("not_exist.json").load();
+    assert!(matches!(err, Err(ConfigError::Io(..))));
+}
+
+#[test]
+fn missing() {
+    let err = Config::<Settings>::new()
+        .str(Format::Json, r#"{"server": {"port": 1}}"#)
+        .load();
+    match err {
+        Err(ConfigError::Missing(e)) => assert_eq!(e.fields(), ["name", "server.host"]),
+        _ => unreachable!(),
+    }
+
+    let err = Config::<Settings>::new()
+        .str(Format::Json, r#"{"name": 1}"#)
+        .load();
+    assert!(matches!(err, Err(ConfigError::Parse { .. })));
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial, serde)]
+struct Account {
+    #[fallback(base_only)]
+    id: u32,
+    name: String,
+}
+
+#[test]
+fn base_only() {
+    let loaded = Config::new()
+        .str(Format::Json, r#"{"id": 1, "name": "json"}"#)
+        .defaults(Account {
+            id: 7,
+            name: "app".to_string(),
+        })
+        .load()
+        .unwrap();
+    assert_eq!(loaded.value.id, 7);
+    assert_eq!(loaded.value.name, "json");
+    assert_eq!(loaded.source_of("id"), Some("defaults"));
+    assert_eq!(loaded.source_of("name"), Some("json string"));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml() {
+    let loaded = Config::new()
+        .str(Format::Toml, "[server]\nport = 8080\n")
+        .defaults(defaults())
+        .load()
+        .unwrap();
+    assert_eq!(loaded.value.server.port, 8080);
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml() {
+    let loaded = Config::new()
+        .str(Format::Yaml, "server:\n  port: 8080\n")
+        .defaults(defaults())
+        .load()
+        .unwrap();
+    assert_eq!(loaded.value.server.port, 8080);
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 use fallback::*;
 
 #[derive(FallbackSpec)]
@@ -19,3 +21,944 @@ fn derive() {
     assert_eq!(data.data1.unzip(), (None, Some(123)));
     assert_eq!(data.data2.unzip(), (None, Some("Hello".to_string())));
 }
+
+#[test]
+fn provenance() {
+    let data = Foo {
+        data1: 123,
+        data2: "Hello".to_string(),
+    };
+
+    let data = Fallback::new(None, Some(data));
+    let data = data.spec();
+
+    assert_eq!(
+        data.provenance(),
+        [
+            ("data1".to_string(), Some(Source::Base)),
+            ("data2".to_string(), Some(Source::Base)),
+        ]
+    );
+}
+
+#[test]
+fn derive_chain() {
+    let data = FallbackChain::new(vec![
+        None,
+        Some(Foo {
+            data1: 123,
+            data2: String::new(),
+        }),
+        Some(Foo {
+            data1: 456,
+            data2: "Hello".to_string(),
+        }),
+    ]);
+    let data = data.spec();
+
+    assert_eq!(
+        data.provenance(),
+        [
+            ("data1".to_string(), Some(1)),
+            ("data2".to_string(), Some(1))
+        ]
+    );
+    assert_eq!(data.data1.into_layers(), [None, Some(123), Some(456)]);
+    assert_eq!(data.data2.and_any_str(), Some("Hello".to_string()));
+}
+
+#[test]
+fn resolve() {
+    let data = Foo {
+        data1: 123,
+        data2: "Hello".to_string(),
+    };
+
+    let data = Fallback::new(None, Some(data));
+    let data = Foo::try_from(data.spec()).unwrap();
+
+    assert_eq!(data.data1, 123);
+    assert_eq!(data.data2, "Hello");
+
+    let data = FallbackChain::<Foo>::new(vec![None, None]);
+    let err = data.spec().resolve().err().unwrap();
+
+    assert_eq!(err.fields(), ["data1", "data2"]);
+    assert_eq!(err.to_string(), "missing fields: data1, data2");
+}
+
+#[derive(FallbackSpec)]
+struct Bar {
+    name: String,
+    #[fallback(nested)]
+    inner: Foo,
+}
+
+#[test]
+fn nested() {
+    let data = Bar {
+        name: "data".to_string(),
+        inner: Foo {
+            data1: 123,
+            data2: String::new(),
+        },
+    };
+    let base_data = Bar {
+        name: "base".to_string(),
+        inner: Foo {
+            data1: 456,
+            data2: "Hello".to_string(),
+        },
+    };
+
+    let data = Fallback::new(Some(data), Some(base_data)).spec();
+
+    assert_eq!(
+        data.provenance(),
+        [
+            ("name".to_string(), Some(Source::Data)),
+            ("inner.data1".to_string(), Some(Source::Data)),
+            ("inner.data2".to_string(), Some(Source::Data)),
+        ]
+    );
+    assert_eq!(data.inner.data1.unzip(), (Some(123), Some(456)));
+    assert_eq!(data.inner.data2.and_any_str(), Some("Hello".to_string()));
+
+    let data = FallbackChain::<Bar>::new(vec![None]).spec();
+    let err = data.resolve().err().unwrap();
+
+    assert_eq!(err.fields(), ["name", "inner.data1", "inner.data2"]);
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Baz {
+    name: String,
+    #[fallback(nested)]
+    inner: Qux,
+}
+
+#[derive(Debug, PartialEq, FallbackSpec)]
+#[fallback(partial = "QuxLayer")]
+struct Qux {
+    data1: i32,
+    data2: String,
+}
+
+#[test]
+fn partial() {
+    let user = PartialBaz {
+        inner: QuxLayer {
+            data1: Some(123),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let project = PartialBaz {
+        name: Some("project".to_string()),
+        inner: QuxLayer {
+            data1: Some(456),
+            data2: None,
+        },
+    };
+    assert_eq!(user.present_fields(), ["inner.data1"]);
+    assert_eq!(project.present_fields(), ["name", "inner.data1"]);
+    let data = PartialBaz::from_layers([user, project]);
+
+    let spec = data.spec(None);
+    assert_eq!(spec.inner.data1.as_ref().unzip(), (Some(&123), None));
+    assert_eq!(
+        spec.resolve().err().unwrap().fields(),
+        ["inner.data2".to_string()]
+    );
+
+    let base = Baz {
+        name: "base".to_string(),
+        inner: Qux {
+            data1: 0,
+            data2: "Hello".to_string(),
+        },
+    };
+    let data = PartialBaz::from(base).resolve(None).unwrap();
+    assert_eq!(data.name, "base");
+    assert_eq!(data.inner.data1, 0);
+    assert_eq!(data.inner.data2, "Hello");
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Pair(i32, #[fallback(nested)] Qux);
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Unit;
+
+#[test]
+fn tuple() {
+    let data = Pair(
+        123,
+        Qux {
+            data1: 456,
+            data2: String::new(),
+        },
+    );
+
+    let data = Fallback::new(None, Some(data)).spec();
+    assert_eq!(
+        data.provenance(),
+        [
+            ("0".to_string(), Some(Source::Base)),
+            ("1.data1".to_string(), Some(Source::Base)),
+            ("1.data2".to_string(), Some(Source::Base)),
+        ]
+    );
+    assert_eq!(data.0.as_ref().unzip(), (None, Some(&123)));
+
+    let data = data.resolve().unwrap();
+    assert_eq!(data.0, 123);
+    assert_eq!(data.1.data1, 456);
+
+    let data = PartialPair(Some(1), QuxLayer::default());
+    assert_eq!(data.present_fields(), ["0"]);
+}
+
+#[test]
+fn unit() {
+    let data = Fallback::<Unit>::new(None, None).spec();
+    assert!(data.provenance().is_empty());
+    assert!(data.resolve().is_ok());
+    assert!(FallbackChain::<Unit>::new(vec![]).spec().resolve().is_ok());
+    assert!(PartialUnit.resolve(None).is_ok());
+}
+
+#[derive(Debug, PartialEq, FallbackSpec)]
+enum Proto {
+    Tcp {
+        port: u16,
+        #[fallback(nested)]
+        inner: Qux,
+    },
+    Udp(u16),
+    Unix,
+}
+
+#[test]
+fn variants() {
+    let data = Proto::Tcp {
+        port: 80,
+        inner: Qux {
+            data1: 123,
+            data2: String::new(),
+        },
+    };
+    let base_data = Proto::Udp(53);
+
+    let data = Fallback::new(None, Some(data)).spec();
+    assert_eq!(
+        data.provenance(),
+        [
+            (String::new(), Some(Source::Base)),
+            ("port".to_string(), Some(Source::Base)),
+            ("inner.data1".to_string(), Some(Source::Base)),
+            ("inner.data2".to_string(), Some(Source::Base)),
+        ]
+    );
+    match data.resolve().unwrap() {
+        Proto::Tcp { port, inner } => {
+            assert_eq!(port, 80);
+            assert_eq!(inner.data1, 123);
+        }
+        _ => unreachable!(),
+    }
+
+    let data = Fallback::new(Some(Proto::Unix), Some(base_data)).spec();
+    assert_eq!(data.provenance(), [(String::new(), Some(Source::Data))]);
+    assert_eq!(data.resolve().unwrap(), Proto::Unix);
+
+    let data = FallbackChain::new(vec![None, Some(Proto::Udp(53)), Some(Proto::Unix)]).spec();
+    match data {
+        VariantFallbackChain::Variant(__FallbackChainProto::Udp(port), layer) => {
+            assert_eq!(layer, 1);
+            assert_eq!(port.into_layers(), [None, Some(53), None]);
+        }
+        _ => unreachable!(),
+    }
+
+    let err = Fallback::<Proto>::new(None, None)
+        .spec()
+        .resolve()
+        .err()
+        .unwrap();
+    assert_eq!(err.fields(), [""]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, FallbackSpec)]
+#[fallback(on_conflict = "conflict")]
+enum Mode {
+    Auto,
+    Manual(Option<i32>),
+}
+
+#[derive(FallbackSpec)]
+struct Job {
+    name: String,
+    #[fallback(nested)]
+    mode: Mode,
+}
+
+#[test]
+fn variants_conflict() {
+    let data = Fallback::new(Some(Mode::Manual(None)), Some(Mode::Manual(Some(1)))).spec();
+    assert_eq!(data.resolve().unwrap(), Mode::Manual(None));
+
+    let data = Fallback::new(Some(Mode::Auto), Some(Mode::Manual(Some(1)))).spec();
+    assert!(matches!(data, VariantFallback::Conflict { .. }));
+
+    let data = FallbackChain::new(vec![
+        Some(Job {
+            name: "job".to_string(),
+            mode: Mode::Auto,
+        }),
+        Some(Job {
+            name: "base".to_string(),
+            mode: Mode::Manual(None),
+        }),
+    ])
+    .spec();
+    assert_eq!(
+        data.provenance(),
+        [("name".to_string(), Some(0)), ("mode".to_string(), None),]
+    );
+    let err = data.resolve().err().unwrap();
+    assert!(err.fields().is_empty());
+    assert_eq!(err.conflicts(), ["mode"]);
+    assert_eq!(err.to_string(), "conflicting variants: mode");
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Generic<'a, T, const N: usize>
+where
+    T: Clone,
+{
+    name: &'a str,
+    values: [T; N],
+}
+
+#[derive(FallbackSpec)]
+struct GenericNested<T: Clone>(#[fallback(nested)] Generic<'static, T, 1>);
+
+#[derive(Debug, PartialEq, FallbackSpec)]
+enum GenericEither<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[test]
+fn generics() {
+    let data = Generic {
+        name: "data",
+        values: [1, 2],
+    };
+    let base_data = Generic {
+        name: "base",
+        values: [3, 4],
+    };
+    let data = Fallback::new(Some(data), Some(base_data)).spec();
+    assert_eq!(data.values.as_ref().unzip(), (Some(&[1, 2]), Some(&[3, 4])));
+    assert_eq!(data.resolve().unwrap().name, "data");
+
+    let data = PartialGeneric::<i32, 2> {
+        name: None,
+        values: Some([5, 6]),
+    };
+    assert_eq!(data.present_fields(), ["values"]);
+
+    let data = GenericNested(Generic {
+        name: "nested",
+        values: [123],
+    });
+    let data = FallbackChain::new(vec![None, Some(data)]).spec();
+    assert_eq!(data.0.values.fallback(), Some([123]));
+
+    let data = Fallback::new(
+        None,
+        Some(GenericEither::<i32, String>::Right("Hello".to_string())),
+    );
+    assert_eq!(
+        data.spec().resolve().unwrap(),
+        GenericEither::Right("Hello".to_string())
+    );
+}
+
+mod facade {
+    pub use fallback as inner;
+}
+
+mod qualified {
+    #[derive(fallback::FallbackSpec)]
+    pub struct Foo {
+        pub data1: i32,
+        #[fallback(nested)]
+        pub inner: Bar,
+    }
+
+    #[derive(crate::facade::inner::FallbackSpec)]
+    #[fallback(crate = "crate::facade::inner", partial)]
+    pub struct Bar {
+        pub data2: String,
+    }
+
+    #[derive(crate::facade::inner::FallbackSpec)]
+    #[fallback(crate = "crate::facade::inner")]
+    pub enum Baz {
+        A(i32),
+    }
+}
+
+#[test]
+fn crate_path() {
+    let data = qualified::Foo {
+        data1: 123,
+        inner: qualified::Bar {
+            data2: "Hello".to_string(),
+        },
+    };
+    let data = Fallback::new(None, Some(data)).spec().resolve().unwrap();
+    assert_eq!(data.data1, 123);
+    assert_eq!(data.inner.data2, "Hello");
+
+    let data = qualified::PartialBar { data2: None };
+    assert!(data.resolve(None).is_err());
+
+    let data = Fallback::new(Some(qualified::Baz::A(1)), None).spec();
+    assert_eq!(data.provenance().len(), 2);
+}
+
+mod named {
+    use fallback::FallbackSpec;
+
+    #[derive(FallbackSpec)]
+    #[fallback(spec = "FooLayers", chain_spec = "FooChain", spec_vis = "pub")]
+    #[fallback(spec_docs, spec_derive(Debug, Clone))]
+    pub(crate) struct Foo {
+        /// The first data.
+        pub data1: i32,
+        pub data2: String,
+    }
+
+    #[derive(Debug, FallbackSpec)]
+    #[fallback(spec = "ModeVariants", spec_derive(Debug))]
+    pub enum Mode {
+        Auto,
+        Manual(i32),
+    }
+}
+
+#[test]
+fn named_spec() {
+    fn layers(data: named::Foo) -> named::FooLayers {
+        Fallback::new(None, Some(data)).spec()
+    }
+
+    let data = layers(named::Foo {
+        data1: 123,
+        data2: "Hello".to_string(),
+    });
+    let cloned = data.clone();
+    assert_eq!(cloned.resolve().unwrap().data1, 123);
+    assert!(format!("{:?}", data).starts_with("FooLayers"));
+
+    let data: named::FooChain = FallbackChain::<named::Foo>::new(vec![None]).spec();
+    assert!(data.resolve().is_err());
+
+    let data = Fallback::new(Some(named::Mode::Manual(1)), None).spec();
+    match data {
+        VariantFallback::Variant(variants @ named::ModeVariants::Manual(_), _) => {
+            assert!(format!("{:?}", variants).starts_with("Manual"));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Secret {
+    #[fallback(data_only)]
+    token: String,
+    #[fallback(base_only)]
+    id: u32,
+    #[fallback(skip)]
+    cache: Vec<u8>,
+    retries: u8,
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct SkipPair(#[fallback(skip)] u8, String);
+
+#[derive(Debug, PartialEq, FallbackSpec)]
+enum SkipVariant {
+    Tuple(#[fallback(skip)] u8, #[fallback(base_only)] i32),
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct SkipGeneric<'a, T> {
+    #[fallback(skip)]
+    _marker: std::marker::PhantomData<&'a T>,
+    value: Option<&'a T>,
+}
+
+#[test]
+fn field_modes() {
+    let data = Secret {
+        token: "data".to_string(),
+        id: 1,
+        cache: vec![1],
+        retries: 3,
+    };
+    let base_data = Secret {
+        token: "base".to_string(),
+        id: 2,
+        cache: vec![2],
+        retries: 5,
+    };
+    let data = Fallback::new(Some(data), Some(base_data)).spec();
+    assert_eq!(
+        data.provenance(),
+        [
+            ("token".to_string(), Some(Source::Data)),
+            ("id".to_string(), Some(Source::Base)),
+            ("retries".to_string(), Some(Source::Data)),
+        ]
+    );
+    let data = data.resolve().unwrap();
+    assert_eq!(data.token, "data");
+    assert_eq!(data.id, 2);
+    assert!(data.cache.is_empty());
+    assert_eq!(data.retries, 3);
+
+    let data = FallbackChain::new(vec![
+        None,
+        Some(Secret {
+            token: "data".to_string(),
+            id: 1,
+            cache: vec![],
+            retries: 3,
+        }),
+    ])
+    .spec();
+    assert_eq!(
+        data.resolve().err().unwrap().fields(),
+        ["token".to_string()]
+    );
+
+    let data = PartialSecret {
+        token: None,
+        id: Some(1),
+        retries: Some(3),
+    };
+    let base = Secret {
+        token: "base".to_string(),
+        id: 2,
+        cache: vec![],
+        retries: 5,
+    };
+    let err = data.resolve(Some(base)).err().unwrap();
+    assert_eq!(err.fields(), ["token"]);
+
+    let layer = |token: Option<&str>, id| PartialSecret {
+        token: token.map(str::to_string),
+        id,
+        retries: None,
+    };
+    let data = PartialSecret::from_layers([
+        layer(Some("data"), Some(1)),
+        layer(None, Some(3)),
+        layer(None, None),
+    ]);
+    assert_eq!(data.present_fields(), ["token"]);
+    assert_eq!(data.present_base_fields(), ["id"]);
+    let base = Secret {
+        token: "base".to_string(),
+        id: 2,
+        cache: vec![],
+        retries: 5,
+    };
+    assert_eq!(data.clone().resolve(Some(base)).unwrap().id, 2);
+    let err = data.clone().resolve(None).err().unwrap();
+    assert_eq!(err.fields(), ["retries"]);
+    let data = data.spec(None);
+    assert_eq!(data.id.source(), Some(Source::Base));
+    assert_eq!(data.id.fallback(), Some(3));
+
+    let data = Fallback::new(None, Some(SkipPair(1, "Hello".to_string()))).spec();
+    assert_eq!(data.0.as_ref().fallback(), Some(&"Hello".to_string()));
+    let data = data.resolve().unwrap();
+    assert_eq!((data.0, data.1.as_str()), (0, "Hello"));
+    assert_eq!(PartialSkipPair(None).present_fields(), Vec::<String>::new());
+
+    let data = Fallback::new(
+        Some(SkipVariant::Tuple(1, 2)),
+        Some(SkipVariant::Tuple(3, 4)),
+    );
+    assert_eq!(data.spec().resolve().unwrap(), SkipVariant::Tuple(0, 4));
+
+    let value = SkipGeneric {
+        _marker: std::marker::PhantomData,
+        value: Some(&1),
+    };
+    let data = Fallback::new(None, Some(value)).spec();
+    assert_eq!(data.resolve().unwrap().value, Some(&1));
+}
+
+fn is_positive(value: &i32) -> bool {
+    *value > 0
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Predicates {
+    #[fallback(empty_is_none)]
+    name: String,
+    #[fallback(empty_is_none)]
+    tags: Vec<String>,
+    #[fallback(when = "is_positive")]
+    port: i32,
+}
+
+#[test]
+fn predicates() {
+    let data = Predicates {
+        name: String::new(),
+        tags: vec![],
+        port: 0,
+    };
+    let base_data = Predicates {
+        name: "base".to_string(),
+        tags: vec!["a".to_string()],
+        port: 80,
+    };
+    let data = Fallback::new(Some(data), Some(base_data)).spec();
+    assert_eq!(
+        data.provenance(),
+        [
+            ("name".to_string(), Some(Source::Base)),
+            ("tags".to_string(), Some(Source::Base)),
+            ("port".to_string(), Some(Source::Base)),
+        ]
+    );
+    let data = data.resolve().unwrap();
+    assert_eq!(data.name, "base");
+    assert_eq!(data.tags, ["a"]);
+    assert_eq!(data.port, 80);
+
+    let data = FallbackChain::new(vec![
+        Some(Predicates {
+            name: String::new(),
+            tags: vec![],
+            port: -1,
+        }),
+        None,
+    ])
+    .spec();
+    assert_eq!(
+        data.resolve().err().unwrap().fields(),
+        ["name", "tags", "port"]
+    );
+
+    let user = PartialPredicates {
+        name: Some(String::new()),
+        tags: None,
+        port: Some(8080),
+    };
+    let project = PartialPredicates {
+        name: Some("project".to_string()),
+        tags: Some(vec![]),
+        port: None,
+    };
+    assert_eq!(user.present_fields(), ["port"]);
+    let data = PartialPredicates::from_layers([user, project]);
+    assert_eq!(data.name.as_deref(), Some("project"));
+    assert_eq!(data.resolve(None).err().unwrap().fields(), ["tags"]);
+}
+
+fn max(data: u32, base: u32) -> u32 {
+    data.max(base)
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Merges {
+    #[fallback(merge)]
+    name: String,
+    #[fallback(merge = "append")]
+    plugins: Vec<String>,
+    #[fallback(merge = "union")]
+    env: std::collections::HashMap<String, String>,
+    #[fallback(merge = "deep")]
+    paths: std::collections::BTreeMap<String, Vec<String>>,
+    #[fallback(merge = "max", when = "is_positive_u32")]
+    retries: u32,
+}
+
+fn is_positive_u32(value: &u32) -> bool {
+    *value > 0
+}
+
+#[test]
+fn merges() {
+    let layer = |name: &str, plugin: &str, retries| Merges {
+        name: name.to_string(),
+        plugins: vec![plugin.to_string()],
+        env: [("KEY".to_string(), plugin.to_string())].into(),
+        paths: [("bin".to_string(), vec![plugin.to_string()])].into(),
+        retries,
+    };
+    let data = Fallback::new(Some(layer("b", "data", 0)), Some(layer("a", "base", 3)))
+        .spec()
+        .resolve()
+        .unwrap();
+    assert_eq!(data.name, "ab");
+    assert_eq!(data.plugins, ["base", "data"]);
+    assert_eq!(data.env["KEY"], "data");
+    assert_eq!(data.paths["bin"], ["base", "data"]);
+    assert_eq!(data.retries, 3);
+
+    let data = FallbackChain::new(vec![
+        Some(layer("c", "user", 5)),
+        None,
+        Some(layer("b", "project", 2)),
+        Some(layer("a", "system", 7)),
+    ])
+    .spec()
+    .resolve()
+    .unwrap();
+    assert_eq!(data.name, "abc");
+    assert_eq!(data.plugins, ["system", "project", "user"]);
+    assert_eq!(data.env["KEY"], "user");
+    assert_eq!(data.retries, 7);
+
+    let user = PartialMerges {
+        plugins: Some(vec!["user".to_string()]),
+        ..Default::default()
+    };
+    let project = PartialMerges {
+        name: Some("project".to_string()),
+        plugins: Some(vec!["project".to_string()]),
+        ..Default::default()
+    };
+    let data = PartialMerges::from_layers([user, project]);
+    assert_eq!(data.name.as_deref(), Some("project"));
+    assert_eq!(
+        data.plugins.as_deref(),
+        Some(&["project".to_string(), "user".to_string()][..])
+    );
+    let data = data.resolve(Some(layer("", "default", 1))).unwrap();
+    assert_eq!(data.plugins, ["default", "project", "user"]);
+    assert_eq!(data.env["KEY"], "default");
+}
+
+struct NoTraits;
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+struct Opaque {
+    value: NoTraits,
+    name: String,
+}
+
+#[derive(FallbackSpec)]
+struct HashParam<H> {
+    value: H,
+}
+
+#[test]
+fn std_traits() {
+    let qux = |data1| Qux {
+        data1,
+        data2: "Hello".to_string(),
+    };
+    let spec = Fallback::new(Some(qux(1)), None).spec();
+    assert_eq!(spec.clone(), spec);
+    assert_ne!(spec, Fallback::new(None, Some(qux(1))).spec());
+    assert!(spec < Fallback::new(Some(qux(2)), None).spec());
+    assert_eq!(spec.cmp(&spec.clone()), std::cmp::Ordering::Equal);
+    assert_eq!(
+        format!("{:?}", spec),
+        "__FallbackQux { \
+            data1: Fallback { data: Some(1), base_data: None }, \
+            data2: Fallback { data: Some(\"Hello\"), base_data: None } \
+        }"
+    );
+    let empty: <Qux as FallbackSpec>::SpecType = Default::default();
+    assert!(empty.resolve().is_err());
+
+    let chain = FallbackChain::new(vec![None, Some(qux(1))]).spec();
+    assert_eq!(chain.clone(), chain);
+
+    let layer = QuxLayer::from(qux(1));
+    assert_eq!(
+        layer,
+        QuxLayer {
+            data1: Some(1),
+            data2: Some("Hello".to_string()),
+        }
+    );
+    assert_eq!(
+        format!("{:?}", QuxLayer::default()),
+        "QuxLayer { data1: None, data2: None }"
+    );
+
+    let left = || Fallback::new(Some(GenericEither::<i32, String>::Left(1)), None).spec();
+    let right = || {
+        Fallback::new(
+            None,
+            Some(GenericEither::<i32, String>::Right("1".to_string())),
+        )
+        .spec()
+    };
+    assert_ne!(left(), right());
+    let variants = |spec| match spec {
+        VariantFallback::Variant(variants, _) => variants,
+        _ => unreachable!(),
+    };
+    let set = std::collections::HashSet::from([variants(left()), variants(left())]);
+    assert_eq!(set.len(), 1);
+    let auto = Fallback::new(Some(Mode::Auto), None).spec();
+    let copied = auto;
+    assert_eq!(auto, copied);
+    assert!(auto < Fallback::new(None, Some(Mode::Auto)).spec());
+    assert!(auto < Fallback::new(Some(Mode::Manual(None)), None).spec());
+    assert_eq!(VariantFallback::<Mode>::default(), VariantFallback::None);
+    let param = || Fallback::new(Some(HashParam { value: 1 }), None).spec();
+    let set = std::collections::HashSet::from([param(), param()]);
+    assert_eq!(set.len(), 1);
+    assert!(variants(left()) < variants(right()));
+    let left2 = Fallback::new(Some(GenericEither::<i32, String>::Left(2)), None).spec();
+    assert!(variants(left()) < variants(left2));
+
+    let opaque = Opaque {
+        value: NoTraits,
+        name: "opaque".to_string(),
+    };
+    let spec = Fallback::new(Some(opaque), None).spec();
+    assert_eq!(spec.name.fallback().as_deref(), Some("opaque"));
+}
+
+#[test]
+fn entries() {
+    let qux = |data1, data2: &str| Qux {
+        data1,
+        data2: data2.to_string(),
+    };
+    let mut spec = Fallback::new(None, Some(Pair(1, qux(2, "base")))).spec();
+    spec.entry_0().set(10);
+    spec.1.entry_data2().and_modify(|s| s.push('!'));
+    assert_eq!(spec.1.entry_data1().get(), Some(&2));
+    assert_eq!(spec.1.entry_data1().source(), Some(Source::Base));
+    let data = spec.resolve().unwrap();
+    assert_eq!(data.0, 10);
+    assert_eq!(data.1, qux(2, "base!"));
+
+    let mut spec = Fallback::new(
+        Some(Generic {
+            name: "data",
+            values: [1],
+        }),
+        Some(Generic {
+            name: "base",
+            values: [2],
+        }),
+    )
+    .spec();
+    assert_eq!(spec.entry_name().clear(), Some("data"));
+    assert_eq!(spec.entry_values().promote(), Some(&mut [1]));
+    assert_eq!(
+        spec.provenance()[0],
+        ("name".to_string(), Some(Source::Base))
+    );
+
+    let secret = |token: &str, id| Secret {
+        token: token.to_string(),
+        id,
+        cache: vec![],
+        retries: 1,
+    };
+    let mut spec = Fallback::new(Some(secret("data", 1)), Some(secret("base", 2))).spec();
+    spec.entry_token().set("edited".to_string());
+    let data = spec.resolve().unwrap();
+    assert_eq!(data.token, "edited");
+    assert_eq!(data.id, 2);
+}
+
+#[allow(dead_code)]
+mod no_prelude {
+    #![no_implicit_prelude]
+
+    pub struct Some;
+    pub struct None;
+
+    #[derive(::fallback::FallbackSpec)]
+    #[fallback(partial)]
+    pub struct Hygienic {
+        #[fallback(empty_is_none)]
+        pub name: ::std::string::String,
+        #[fallback(merge)]
+        pub tags: ::std::vec::Vec<::std::string::String>,
+        #[fallback(base_only)]
+        pub id: u32,
+        #[fallback(skip)]
+        pub cache: u8,
+        #[fallback(nested)]
+        pub inner: Inner,
+    }
+
+    #[derive(::fallback::FallbackSpec)]
+    #[fallback(partial)]
+    pub struct Inner(pub i32);
+
+    #[derive(::fallback::FallbackSpec)]
+    pub enum HygienicEnum {
+        Unit,
+        Tuple(i32),
+        Named {
+            #[fallback(nested)]
+            inner: Inner,
+        },
+    }
+}
+
+#[test]
+fn no_implicit_prelude() {
+    let data = no_prelude::Hygienic {
+        name: String::new(),
+        tags: vec!["data".to_string()],
+        id: 1,
+        cache: 1,
+        inner: no_prelude::Inner(1),
+    };
+    let base_data = no_prelude::Hygienic {
+        name: "base".to_string(),
+        tags: vec!["base".to_string()],
+        id: 2,
+        cache: 2,
+        inner: no_prelude::Inner(2),
+    };
+    let data = Fallback::new(Some(data), Some(base_data))
+        .spec()
+        .resolve()
+        .unwrap();
+    assert_eq!(data.name, "base");
+    assert_eq!(data.tags, ["base", "data"]);
+    assert_eq!(data.id, 2);
+    assert_eq!(data.cache, 0);
+    assert_eq!(data.inner.0, 1);
+
+    let data = Fallback::new(None, Some(no_prelude::HygienicEnum::Tuple(1)));
+    assert!(matches!(
+        data.spec().resolve(),
+        Ok(no_prelude::HygienicEnum::Tuple(1))
+    ));
+}
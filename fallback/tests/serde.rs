@@ -0,0 +1,137 @@
+#![cfg(feature = "serde")]
+
+use fallback::*;
+
+#[derive(FallbackSpec, serde::Serialize, serde::Deserialize)]
+#[fallback(partial, serde)]
+struct Foo {
+    data1: i32,
+    #[serde(rename = "name")]
+    data2: String,
+}
+
+#[derive(FallbackSpec)]
+#[fallback(partial, serde)]
+struct Bar {
+    #[fallback(nested)]
+    inner: Foo,
+    enabled: bool,
+}
+
+#[test]
+fn pair() {
+    let data = Fallback::new(None, Some(123));
+    assert_eq!(
+        serde_json::to_string(&data).unwrap(),
+        r#"{"data":null,"base":123}"#
+    );
+
+    let data: Fallback<i32> = serde_json::from_str(r#"{"data":456}"#).unwrap();
+    assert_eq!(data.unzip(), (Some(456), None));
+
+    let data: FallbackChain<i32> = serde_json::from_str("[null, 1, 2]").unwrap();
+    assert_eq!(data.into_layers(), [None, Some(1), Some(2)]);
+}
+
+#[test]
+fn spec() {
+    let data = Fallback::new(
+        None,
+        Some(Foo {
+            data1: 123,
+            data2: "Hello".to_string(),
+        }),
+    )
+    .spec();
+    assert_eq!(
+        serde_json::to_string(&data).unwrap(),
+        r#"{"data1":{"data":null,"base":123},"name":{"data":null,"base":"Hello"}}"#
+    );
+}
+
+#[test]
+fn sparse_spec() {
+    let data: <Foo as FallbackSpec>::SpecType =
+        serde_json::from_str(r#"{"name":{"data":"a"}}"#).unwrap();
+    assert_eq!(data.data1, Fallback::new(None, None));
+    assert_eq!(data.data2, Fallback::new(Some("a".to_string()), None));
+
+    let data: <Bar as FallbackChainSpec>::ChainSpecType =
+        serde_json::from_str(r#"{"enabled":[true]}"#).unwrap();
+    assert!(data.inner.data1.into_layers().is_empty());
+    assert_eq!(data.enabled.into_layers(), [Some(true)]);
+}
+
+#[test]
+fn sparse() {
+    let user: PartialBar = serde_json::from_str(r#"{"inner": {"name": "user"}}"#).unwrap();
+    let project: PartialBar = serde_json::from_str(r#"{"enabled": true}"#).unwrap();
+    let base = Bar {
+        inner: Foo {
+            data1: 123,
+            data2: "Hello".to_string(),
+        },
+        enabled: false,
+    };
+
+    let data = PartialBar::from_layers([user, project])
+        .resolve(Some(base))
+        .unwrap();
+    assert_eq!(data.inner.data1, 123);
+    assert_eq!(data.inner.data2, "user");
+    assert!(data.enabled);
+}
+
+#[derive(Debug, PartialEq, FallbackSpec, serde::Serialize, serde::Deserialize)]
+#[fallback(serde)]
+enum Proto {
+    Tcp { port: u16 },
+    Udp(u16),
+}
+
+#[derive(FallbackSpec)]
+#[fallback(serde)]
+struct Endpoint {
+    host: String,
+    #[fallback(nested)]
+    proto: Proto,
+}
+
+#[test]
+fn nested_enum() {
+    let data = Fallback::new(
+        None,
+        Some(Endpoint {
+            host: "localhost".to_string(),
+            proto: Proto::Udp(53),
+        }),
+    )
+    .spec();
+    let json = serde_json::to_string(&data).unwrap();
+    assert_eq!(
+        json,
+        r#"{"host":{"data":null,"base":"localhost"},"proto":{"Variant":[{"Udp":{"data":null,"base":53}},"Base"]}}"#
+    );
+    let data: <Endpoint as FallbackSpec>::SpecType = serde_json::from_str(&json).unwrap();
+    assert_eq!(data.resolve().unwrap().proto, Proto::Udp(53));
+
+    let data: <Endpoint as FallbackSpec>::SpecType =
+        serde_json::from_str(r#"{"host":{"data":"a"}}"#).unwrap();
+    assert!(matches!(data.proto, VariantFallback::None));
+}
+
+mod facade {
+    pub use fallback as inner;
+}
+
+#[derive(facade::inner::FallbackSpec)]
+#[fallback(partial, serde, crate = "facade::inner")]
+struct Baz {
+    data1: i32,
+}
+
+#[test]
+fn crate_path() {
+    let data: PartialBaz = serde_json::from_str(r#"{"data1":1}"#).unwrap();
+    assert_eq!(data.resolve(None).unwrap().data1, 1);
+}
@@ -0,0 +1,6 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+enum Foo {}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+struct Account {
+    #[fallback(base_only)]
+    id: u32,
+    name: String,
+}
+
+fn main() {
+    let mut spec = Fallback::<Account>::new(None, None).spec();
+    spec.entry_name().set("name".to_string());
+    spec.entry_id().set(99);
+}
@@ -0,0 +1,29 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+struct Foo {
+    #[fallback(skip, data_only)]
+    data1: i32,
+}
+
+#[derive(FallbackSpec)]
+struct Bar {
+    #[fallback(nested, base_only)]
+    data1: Foo,
+}
+
+#[derive(FallbackSpec)]
+struct Baz {
+    #[fallback(empty_is_none, when = "String::is_empty")]
+    data1: String,
+    #[fallback(skip, empty_is_none)]
+    data2: String,
+}
+
+#[derive(FallbackSpec)]
+struct Qux {
+    #[fallback(merge = "append", data_only)]
+    data1: Vec<i32>,
+}
+
+fn main() {}
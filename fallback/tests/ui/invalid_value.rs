@@ -0,0 +1,16 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+#[fallback(partial = "1Foo")]
+struct Foo {
+    data1: i32,
+}
+
+#[derive(FallbackSpec)]
+#[fallback(on_conflict = "panic")]
+enum Bar {
+    A(i32),
+    B(String),
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+#[fallback(partial)]
+enum Foo {
+    A(i32),
+    B(String),
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use fallback::*;
+use std::marker::PhantomData;
+
+#[derive(FallbackSpec)]
+struct Foo<T> {
+    #[fallback(skip)]
+    _marker: PhantomData<T>,
+    data1: i32,
+}
+
+#[derive(FallbackSpec)]
+enum Bar<'a> {
+    Borrowed(#[fallback(skip)] &'a str),
+    Owned(String),
+}
+
+fn main() {}
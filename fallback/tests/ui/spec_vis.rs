@@ -0,0 +1,21 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+#[fallback(spec_vis = "pub(crate)")]
+pub struct Foo {
+    data1: i32,
+}
+
+#[derive(FallbackSpec)]
+#[fallback(spec_vis = "pub(super)")]
+pub(crate) enum Bar {
+    Baz(i32),
+}
+
+#[derive(FallbackSpec)]
+#[fallback(spec_vis = "pub")]
+struct Qux {
+    data1: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+union Foo {
+    data1: i32,
+    data2: u32,
+}
+
+fn main() {}
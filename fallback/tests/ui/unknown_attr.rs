@@ -0,0 +1,23 @@
+use fallback::*;
+
+#[derive(FallbackSpec)]
+#[fallback(unknown)]
+struct Foo {
+    data1: i32,
+}
+
+#[derive(FallbackSpec)]
+struct Bar {
+    #[fallback(flatten)]
+    data1: i32,
+    #[fallback(nested = true)]
+    data2: Foo,
+}
+
+#[derive(FallbackSpec)]
+enum Baz {
+    #[fallback(nested)]
+    A(i32),
+}
+
+fn main() {}